@@ -0,0 +1,61 @@
+// Double vision: draws one offscreen render layer back onto the screen, wobbling,
+// doubled up (with the second image split into red and blue) and blurred.
+// See double_vision.rs; the DoubleVision struct must match DoubleVisionUniform there.
+
+#import bevy_sprite::{
+	mesh2d_vertex_output::VertexOutput,
+	mesh2d_view_bindings::globals,
+}
+
+struct DoubleVision {
+	offset: vec2<f32>,
+	strength: f32,
+	wobble_amplitude: f32,
+	wobble_frequency: f32,
+	wobble_wavelength: f32,
+	blur: f32,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> double_vision: DoubleVision;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var layer_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var layer_sampler: sampler;
+
+const TAU: f32 = 6.28318530718;
+
+// A small cross-shaped blur; radius in texels (0 is a plain sample).
+fn blurred(uv: vec2<f32>, texel: vec2<f32>, radius: f32) -> vec4<f32> {
+	let reach = texel * radius;
+	var color = textureSample(layer_texture, layer_sampler, uv) * 0.4;
+	color += textureSample(layer_texture, layer_sampler, uv + vec2(reach.x, 0.)) * 0.15;
+	color += textureSample(layer_texture, layer_sampler, uv - vec2(reach.x, 0.)) * 0.15;
+	color += textureSample(layer_texture, layer_sampler, uv + vec2(0., reach.y)) * 0.15;
+	color += textureSample(layer_texture, layer_sampler, uv - vec2(0., reach.y)) * 0.15;
+	return color;
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+	let strength = double_vision.strength;
+	let size = vec2<f32>(textureDimensions(layer_texture));
+	let texel = 1. / size;
+	let time = globals.time;
+
+	// Rows slide from side to side on a sine wave travelling down the layer.
+	let row = mesh.uv.y * size.y / max(double_vision.wobble_wavelength, 1.);
+	let phase = (row + time * double_vision.wobble_frequency) * TAU;
+	let wobble = vec2(sin(phase), 0.) * double_vision.wobble_amplitude * strength;
+	let uv = mesh.uv + wobble * texel;
+
+	let radius = double_vision.blur * strength;
+	let base = blurred(uv, texel, radius);
+
+	// The second image drifts in and out around its offset, red and blue pulling slightly apart.
+	let drift = double_vision.offset * strength * (0.75 + 0.25 * sin(time * 1.3));
+	let red = blurred(uv + drift * texel, texel, radius);
+	let blue = blurred(uv + drift * 1.2 * texel, texel, radius);
+	let second = vec4(red.r, (red.g + blue.g) * 0.5, blue.b, max(red.a, blue.a));
+
+	// The layer was drawn onto a transparent image, so its colors come out premultiplied.
+	let color = mix(base, second, 0.5 * clamp(strength, 0., 1.));
+	return vec4(color.rgb / max(color.a, 0.0001), color.a);
+}
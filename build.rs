@@ -0,0 +1,24 @@
+fn main() {
+	println!("cargo:rerun-if-changed=build.rs");
+	println!("cargo:rerun-if-env-changed=FMOD_LIB_DIR");
+
+	// FMOD is only linked with the "fmod" feature (see src/audio/fmod_backend.rs).
+	if std::env::var_os("CARGO_FEATURE_FMOD").is_some() {
+		// vendor/fmod only has the macOS libraries. Anywhere else, set FMOD_LIB_DIR to the FMOD Engine's
+		// studio and core lib directories for the platform (separated like PATH).
+		match std::env::var_os("FMOD_LIB_DIR") {
+			Some(dirs) => {
+				for dir in std::env::split_paths(&dirs) {
+					println!("cargo:rustc-link-search=native={}", dir.display());
+				}
+			}
+			None => {
+				let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("cargo sets CARGO_MANIFEST_DIR");
+				println!("cargo:rustc-link-search=native={manifest_dir}/vendor/fmod");
+				if std::env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("macos") {
+					println!("cargo:warning=vendor/fmod only has the macOS FMOD libraries; set FMOD_LIB_DIR to link on this platform");
+				}
+			}
+		}
+	}
+}
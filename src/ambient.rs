@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{
+	Component, Resource,
+	Entity, Name,
+	Query, Res, ResMut, With,
+	Commands,
+	Transform,
+	Vec2,
+};
+
+use crate::{VIRTUAL_RESOLUTION, FeverLevel};
+use crate::audio::{AudioManager, AudioBus, Sound, SoundInstance};
+use crate::cleanup::LivesIn;
+
+// =============================================================================
+// Ambient sound: things in the room around the phone, heard from where they are
+// =============================================================================
+
+// An AmbientEmitter loops its sound from wherever its Transform puts it, in world coordinates
+// (so off-screen is just past the edge of VIRTUAL_RESOLUTION). The player is taken to be at the
+// phone screen: left of it pans left, and the further away, the quieter (see AmbientSettings).
+// Everything goes through the AudioManager, so any backend will do, the null one included.
+//
+// The clock radio is one of these, off to the left. Each fever stage tunes it to a station (or off),
+// with a burst of tuning static whenever it changes.
+
+#[derive(Component, Debug)]
+pub struct AmbientEmitter {
+	pub path: Option<String>,		// None plays nothing (e.g. a radio that's switched off).
+	pub volume: f32,				// Before distance attenuation.
+}
+
+#[derive(Resource, Debug)]
+pub struct AmbientSettings {
+	pub listener: Vec2,				// Where the player hears from.
+	pub pan_distance: f32,			// Sideways distance that pans all the way left or right.
+	pub reference_distance: f32,	// Full volume within this distance...
+	pub rolloff: f32,				// ...then falling off as (reference / distance) to this power.
+	pub change_threshold: f32,		// Pan and volume changes smaller than this aren't sent.
+	pub fade_seconds: f32,			// When an emitter stops or changes sound.
+}
+impl Default for AmbientSettings {
+	fn default() -> Self {
+		Self {
+			listener: Vec2::ZERO,
+			pan_distance: VIRTUAL_RESOLUTION.x as f32,
+			reference_distance: VIRTUAL_RESOLUTION.x as f32 * 0.5,
+			rolloff: 1.,
+			change_threshold: 0.01,
+			fade_seconds: 0.5,
+		}
+	}
+}
+impl AmbientSettings {
+	pub fn pan(&self, position: Vec2) -> f32 {
+		((position.x - self.listener.x) / self.pan_distance.max(f32::EPSILON)).clamp(-1., 1.)
+	}
+
+	pub fn attenuation(&self, position: Vec2) -> f32 {
+		let distance = position.distance(self.listener);
+		let reference = self.reference_distance.max(f32::EPSILON);
+		(reference / distance.max(reference)).powf(self.rolloff)
+	}
+}
+
+#[derive(Debug)]
+struct PlayingAmbient {
+	instance: SoundInstance,
+	path: String,
+	volume: f32,
+	pan: f32,
+}
+
+// What each emitter is playing, so it can be stopped once the emitter is gone.
+#[derive(Resource, Debug, Default)]
+pub struct AmbientPlayback(HashMap<Entity, PlayingAmbient>);
+
+pub fn play_ambient_emitters(
+	mut audio: ResMut<AudioManager>,
+	settings: Res<AmbientSettings>,
+	mut playback: ResMut<AmbientPlayback>,
+	emitters: Query<(Entity, &AmbientEmitter, &Transform)>,
+) {
+	// Despawned emitters, and ones that changed sound or went quiet, stop.
+	playback.0.retain(|entity, playing| {
+		let keep = emitters.get(*entity).is_ok_and(|(_, emitter, _)| emitter.path.as_ref() == Some(&playing.path));
+		if !keep {
+			audio.stop(playing.instance, settings.fade_seconds);
+		}
+		keep
+	});
+
+	for (entity, emitter, transform) in &emitters {
+		let Some(path) = &emitter.path else {
+			continue;
+		};
+		let position = transform.translation.truncate();
+		let volume = emitter.volume * settings.attenuation(position);
+		let pan = settings.pan(position);
+
+		let Some(playing) = playback.0.get_mut(&entity) else {
+			let sound = Sound::new(path.clone(), AudioBus::Sfx).with_volume(volume).with_pan(pan).looping();
+			let instance = audio.play(sound);
+			playback.0.insert(entity, PlayingAmbient { instance, path: path.clone(), volume, pan });
+			continue;
+		};
+		if (playing.volume - volume).abs() > settings.change_threshold {
+			audio.set_volume(playing.instance, volume);
+			playing.volume = volume;
+		}
+		if (playing.pan - pan).abs() > settings.change_threshold {
+			audio.set_pan(playing.instance, pan);
+			playing.pan = pan;
+		}
+	}
+}
+
+// -----------------------------------------------------------------------------
+// Clock radio
+// -----------------------------------------------------------------------------
+
+#[derive(Component, Debug)]
+pub struct Radio;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RadioStage {
+	pub station: Option<&'static str>,		// A sound (path) to loop; None is off.
+	pub volume: f32,
+}
+impl RadioStage {
+	const CALM: Self = Self {
+		station: None,
+		volume: 0.,
+	};
+}
+
+// One entry per FeverLevel; levels past the end use the last entry.
+#[derive(Resource, Debug)]
+pub struct RadioSettings {
+	pub position: Vec2,
+	pub tuning_sound: &'static str,		// Played (from the radio) on every change of station.
+	pub stages: Vec<RadioStage>,
+}
+impl Default for RadioSettings {
+	fn default() -> Self {
+		Self {
+			// Off-screen left, on the nightstand.
+			position: Vec2::new(-(VIRTUAL_RESOLUTION.x as f32) * 0.75, -(VIRTUAL_RESOLUTION.y as f32) * 0.25),
+			tuning_sound: "audio/ambient/radio_tuning.ogg",
+			stages: vec![
+				RadioStage::CALM,
+				RadioStage::CALM,
+				RadioStage { station: Some("audio/ambient/radio_static.ogg"), volume: 0.3 },
+				RadioStage { station: Some("audio/ambient/radio_creepy_music.ogg"), volume: 0.6 },
+				RadioStage { station: Some("audio/ambient/radio_whispers.ogg"), volume: 0.8 },
+			],
+		}
+	}
+}
+impl RadioSettings {
+	pub fn stage(&self, fever_level: usize) -> RadioStage {
+		self.stages.get(fever_level)
+			.or(self.stages.last())
+			.copied()
+			.unwrap_or(RadioStage::CALM)
+	}
+}
+
+pub fn spawn_radio(mut commands: Commands, settings: Res<RadioSettings>) {
+	commands.spawn((
+		Name::new("Radio"),
+		LivesIn::game(),
+		Radio,
+		AmbientEmitter { path: None, volume: 0. },
+		Transform::from_translation(settings.position.extend(0.)),
+	));
+}
+
+// This runs when FeverLevel changes (see App setup).
+pub fn tune_radio(
+	mut audio: ResMut<AudioManager>,
+	fever_level: Res<FeverLevel>,
+	settings: Res<RadioSettings>,
+	ambient_settings: Res<AmbientSettings>,
+	mut radios: Query<(&mut AmbientEmitter, &Transform), With<Radio>>,
+) {
+	let stage = settings.stage(fever_level.0);
+	for (mut emitter, transform) in &mut radios {
+		emitter.volume = stage.volume;
+		let station = stage.station.map(String::from);
+		if emitter.path == station {
+			continue;
+		}
+		emitter.path = station;
+
+		let position = transform.translation.truncate();
+		let volume = ambient_settings.attenuation(position);
+		audio.play(Sound::new(settings.tuning_sound, AudioBus::Sfx).with_volume(volume).with_pan(ambient_settings.pan(position)));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use bevy::prelude::{App, MinimalPlugins, Startup, Update, Transform, Vec2, IntoScheduleConfigs};
+
+	use super::*;
+	use crate::audio::{AudioManagerPlugin, AudioBackend, AudioRequest};
+	use crate::audio::null_backend::NullAudioLog;
+
+	fn ambient_app() -> App {
+		let mut app = App::new();
+		app.add_plugins((MinimalPlugins, AudioManagerPlugin { backend: AudioBackend::Null }))
+		.insert_resource(FeverLevel(0))
+		.init_resource::<AmbientSettings>()
+		.init_resource::<AmbientPlayback>()
+		.init_resource::<RadioSettings>()
+		.add_systems(Startup, spawn_radio)
+		.add_systems(Update, (tune_radio, play_ambient_emitters).chain());
+		app
+	}
+
+	fn take_log(app: &mut App) -> Vec<AudioRequest> {
+		std::mem::take(&mut app.world_mut().resource_mut::<NullAudioLog>().0)
+	}
+
+	fn assert_close(actual: f32, expected: f32) {
+		assert!((actual - expected).abs() < 1e-4, "{actual} isn't {expected}");
+	}
+
+	#[test]
+	fn emitters_pan_and_fade_with_position() {
+		let mut app = ambient_app();
+		let width = VIRTUAL_RESOLUTION.x as f32;
+		// Right of the listener by a whole pan distance: hard right, at twice the reference distance.
+		let emitter = app.world_mut().spawn((
+			AmbientEmitter { path: Some(String::from("audio/ambient/fridge_hum.ogg")), volume: 0.8 },
+			Transform::from_xyz(width, 0., 0.),
+		)).id();
+		app.update();
+
+		let log = take_log(&mut app);
+		let [AudioRequest::PlaySound { instance, sound }] = log.as_slice() else {
+			panic!("expected the emitter to start playing, got {log:?}");
+		};
+		assert_eq!(sound.path, "audio/ambient/fridge_hum.ogg");
+		assert!(sound.looping);
+		assert_close(sound.pan.unwrap_or_default(), 1.);
+		assert_close(sound.volume, 0.8 * 0.5);
+
+		// Halfway to the left, within the reference distance: panned halfway left, at full volume.
+		app.world_mut().entity_mut(emitter).insert(Transform::from_xyz(-width * 0.5, 0., 0.));
+		app.update();
+		let log = take_log(&mut app);
+		assert_eq!(log.len(), 2, "expected a volume and a pan change, got {log:?}");
+		for request in &log {
+			match request {
+				AudioRequest::SetVolume { instance: changed, volume } => {
+					assert_eq!(changed, instance);
+					assert_close(*volume, 0.8);
+				}
+				AudioRequest::SetPan { instance: changed, pan } => {
+					assert_eq!(changed, instance);
+					assert_close(*pan, -0.5);
+				}
+				other => panic!("unexpected request {other:?}"),
+			}
+		}
+
+		// Gone: stopped, with a fade.
+		app.world_mut().entity_mut(emitter).despawn();
+		app.update();
+		let fade_seconds = app.world().resource::<AmbientSettings>().fade_seconds;
+		assert_eq!(take_log(&mut app), vec![AudioRequest::Stop { instance: *instance, fade_seconds }]);
+	}
+
+	#[test]
+	fn radio_tunes_in_with_the_fever() {
+		let mut app = ambient_app();
+		app.update();
+		// Off while calm.
+		assert_eq!(take_log(&mut app), vec![]);
+
+		app.insert_resource(FeverLevel(2));
+		app.update();
+
+		let settings = RadioSettings::default();
+		let stage = settings.stage(2);
+		let ambient_settings = AmbientSettings::default();
+		let expected_pan = -0.75;
+		let expected_attenuation = ambient_settings.reference_distance / settings.position.distance(Vec2::ZERO);
+
+		let log = take_log(&mut app);
+		let [
+			AudioRequest::PlaySound { sound: tuning, .. },
+			AudioRequest::PlaySound { sound: station, .. },
+		] = log.as_slice() else {
+			panic!("expected tuning static and then the station, got {log:?}");
+		};
+		assert_eq!(tuning.path, settings.tuning_sound);
+		assert!(!tuning.looping);
+		assert_close(tuning.pan.unwrap_or_default(), expected_pan);
+		assert_close(tuning.volume, expected_attenuation);
+
+		assert_eq!(Some(station.path.as_str()), stage.station);
+		assert!(station.looping);
+		assert_close(station.pan.unwrap_or_default(), expected_pan);
+		assert_close(station.volume, stage.volume * expected_attenuation);
+	}
+}
@@ -1,36 +1,15 @@
-use bevy::prelude::States;
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
-pub enum AppState {
-    #[default]
-    Splash,
-    MainMenu,
-    InGame,
-    PauseMenu,
-    Won,
-}
+use bevy::prelude::{App, States, OnExit};
 
 // Need to knopw about Cleanup to apply treait bound to outer generic type (see below).
-use crate::cleanup::Cleanup;
+use crate::cleanup::{Cleanup, cleanup_system};
+
+// The states, their marker types and their cleanup all come from the one list below (see app_states!),
+// so a new state only needs adding there.
 
 // ZST / marker types for use with the generic cleanup function found in component_utils.rs.
 // (These are used to give the generic a type, which lets it target entities
-// possessing the corresponding cleanup marker / component.) 
-
-#[derive(Debug, Eq, PartialEq)]
-pub struct Splash;
-
-#[derive(Debug, Eq, PartialEq)]
-pub struct MainMenu;
-
-#[derive(Debug, Eq, PartialEq)]
-pub struct InGame;
-
-#[derive(Debug, Eq, PartialEq)]
-pub struct PauseMenu;
-
-#[derive(Debug, Eq, PartialEq)]
-pub struct Won;
+// possessing the corresponding cleanup marker / component.)
+// Each AppState variant gets one of the same name, e.g. Cleanup<InGame> for AppState::InGame.
 
 // This empty trait is a marker to make sure that we can restrict generics
 // to allow only these types, i.e. when we want to operate on an AppState-associated type
@@ -38,8 +17,32 @@ pub struct Won;
 
 pub trait AppStateTrait {}
 
-impl AppStateTrait for Cleanup<Splash> {}
-impl AppStateTrait for Cleanup<MainMenu> {}
-impl AppStateTrait for Cleanup<InGame> {}
-impl AppStateTrait for Cleanup<PauseMenu> {}
-impl AppStateTrait for Cleanup<Won> {}
\ No newline at end of file
+macro_rules! app_states {
+	($($(#[$attribute:meta])* $state:ident),* $(,)?) => {
+		#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
+		pub enum AppState {
+			$($(#[$attribute])* $state,)*
+		}
+
+		$(
+			#[derive(Debug, Eq, PartialEq)]
+			pub struct $state;
+
+			impl AppStateTrait for Cleanup<$state> {}
+		)*
+
+		// Despawns each state's Cleanup<T> entities on leaving it (see CleanupPlugin).
+		pub fn add_state_cleanup_systems(app: &mut App) {
+			$(app.add_systems(OnExit(AppState::$state), cleanup_system::<Cleanup<$state>>);)*
+		}
+	};
+}
+
+app_states! {
+	#[default]
+	Splash,
+	MainMenu,
+	InGame,
+	PauseMenu,
+	Won,
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::time::Duration;
+
+use bevy::prelude::{
+	App, Component, Resource,
+	Entity, Name,
+	Query, Res, ResMut, Without,
+	Commands,
+	AssetServer, AudioPlayer, AudioSink, AudioSinkPlayback, PlaybackSettings,
+	SpatialAudioSink, SpatialListener,
+	Time, Transform, Startup, PostUpdate, IntoScheduleConfigs,
+	warn,
+};
+
+use bevy::audio::{PlaybackMode, Volume};
+
+use crate::cleanup::{Cleanup, Quit};
+use super::{AudioManager, AudioRequest, AudioBus, Sound, SoundInstance};
+
+// =============================================================================
+// Bevy backend: bevy_audio, one AudioPlayer entity per sound
+// =============================================================================
+
+// bevy_audio has no buses or parameters: bus volumes are multiplied into each sound's volume here,
+// and parameters are ignored.
+// Synced sounds are spawned paused and only started (all from the same spot) once every one of
+// them has loaded. bevy_audio can't start or keep sinks sample-aligned, so that's only as close as a
+// frame: after that, any that drift too far from the group's leader are seeked back in line - which
+// can be heard, so it's done sparingly (see MIN_SYNC_CORRECTION_SECONDS). Stems must all be the same
+// length, so they wrap around together. (The FMOD backend has none of these limits.)
+// Panned sounds are played as spatial audio, placed between the ears of a listener at the origin;
+// they can't be synced.
+
+// How far (seconds) a synced sound may wander from its leader before it is put back in line...
+const MAX_SYNC_DRIFT_SECONDS: f32 = 0.03;
+// ...and how long a group is left alone after that, so a stem that won't stay put stutters at worst this often.
+const MIN_SYNC_CORRECTION_SECONDS: f32 = 2.;
+// Panned sounds sit this far apart at the extremes (pan -1 and 1 land on the ears).
+const EAR_GAP: f32 = 2.;
+
+#[derive(Component, Debug)]
+pub struct BevySound {
+	pub instance: SoundInstance,
+	pub bus: AudioBus,
+	pub volume: f32,
+	pub fade_out: Option<(f32, f32)>,		// (Seconds left, seconds in total) until it is stopped.
+	pub sync_group: Option<SoundInstance>,	// The first sound of the synced group it belongs to.
+	pub seek_to: Option<f32>,				// Waiting to be applied once the sound has a sink.
+	pub pan: Option<f32>,
+}
+impl BevySound {
+	fn fade(&self) -> f32 {
+		match self.fade_out {
+			Some((remaining, total)) => (remaining / total.max(f32::EPSILON)).clamp(0., 1.),
+			None => 1.,
+		}
+	}
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct BevyBusVolumes(HashMap<AudioBus, f32>);
+impl BevyBusVolumes {
+	fn of(&self, bus: AudioBus) -> f32 {
+		let own = self.0.get(&bus).copied().unwrap_or(1.);
+		let master = self.0.get(&AudioBus::Master).copied().unwrap_or(1.);
+		if bus == AudioBus::Master { own } else { own * master }
+	}
+}
+
+#[derive(Debug, Default)]
+pub struct BevySyncGroup {
+	pub members: Vec<SoundInstance>,
+	pub started: bool,
+	pub since_correction: f32,		// Seconds since any member was last put back in line.
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct BevySyncGroups(HashMap<SoundInstance, BevySyncGroup>);
+
+pub(super) fn build(app: &mut App) {
+	app.init_resource::<BevyBusVolumes>()
+	.init_resource::<BevySyncGroups>()
+	.add_systems(Startup, spawn_bevy_listener)
+	.add_systems(PostUpdate, (run_bevy_audio_backend, sync_bevy_sounds).chain());
+}
+
+fn spawn_bevy_listener(mut commands: Commands) {
+	commands.spawn((
+		Name::new("AudioListener"),
+		Cleanup::<Quit>::new(),
+		SpatialListener::new(EAR_GAP),
+		Transform::default(),
+	));
+}
+
+fn run_bevy_audio_backend(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+	time: Res<Time>,
+	mut manager: ResMut<AudioManager>,
+	mut bus_volumes: ResMut<BevyBusVolumes>,
+	mut sync_groups: ResMut<BevySyncGroups>,
+	mut sounds: Query<(Entity, &mut BevySound, &AudioPlayer, Option<&mut AudioSink>, Option<&mut SpatialAudioSink>, Option<&mut Transform>)>,
+) {
+	// Sounds asked for this frame don't have entities yet; requests about them are applied here instead.
+	let mut starting: Vec<(BevySound, Sound)> = Vec::new();
+
+	for request in manager.take_requests() {
+		match request {
+			AudioRequest::PlaySound { instance, sound } => {
+				starting.push((new_sound(instance, &sound), sound));
+			}
+			AudioRequest::PlaySynced { sounds: synced, start_at } => {
+				let Some(leader) = synced.first().map(|(instance, _)| *instance) else {
+					continue;
+				};
+				sync_groups.0.insert(leader, BevySyncGroup {
+					members: synced.iter().map(|(instance, _)| *instance).collect(),
+					..BevySyncGroup::default()
+				});
+				for (instance, sound) in synced {
+					let mut bevy_sound = new_sound(instance, &sound);
+					bevy_sound.sync_group = Some(leader);
+					bevy_sound.seek_to = Some(start_at);
+					starting.push((bevy_sound, sound));
+				}
+			}
+			AudioRequest::Seek { instance, seconds } => {
+				// The whole group moves together.
+				let group = starting.iter().map(|(sound, _)| sound)
+					.chain(sounds.iter().map(|(_, sound, ..)| sound))
+					.find(|sound| sound.instance == instance)
+					.map(|sound| sound.sync_group);
+				let Some(group) = group else {
+					continue;
+				};
+				let in_group = |sound: &BevySound| sound.instance == instance || (group.is_some() && sound.sync_group == group);
+				for (sound, _) in starting.iter_mut().filter(|(sound, _)| in_group(sound)) {
+					sound.seek_to = Some(seconds);
+				}
+				for (_, mut sound, ..) in sounds.iter_mut().filter(|(_, sound, ..)| in_group(sound)) {
+					sound.seek_to = Some(seconds);
+				}
+			}
+			AudioRequest::Stop { instance, fade_seconds } => {
+				for group in sync_groups.0.values_mut() {
+					group.members.retain(|member| *member != instance);
+				}
+				if let Some(index) = starting.iter().position(|(sound, _)| sound.instance == instance) {
+					starting.remove(index);
+				} else if let Some((entity, mut sound, ..)) = sounds.iter_mut().find(|(_, sound, ..)| sound.instance == instance) {
+					if fade_seconds > 0. {
+						sound.fade_out = Some((fade_seconds, fade_seconds));
+					} else {
+						commands.entity(entity).despawn();
+					}
+				}
+			}
+			AudioRequest::SetVolume { instance, volume } => {
+				if let Some((sound, _)) = starting.iter_mut().find(|(sound, _)| sound.instance == instance) {
+					sound.volume = volume;
+				} else if let Some((_, mut sound, ..)) = sounds.iter_mut().find(|(_, sound, ..)| sound.instance == instance) {
+					sound.volume = volume;
+				}
+			}
+			AudioRequest::SetPan { instance, pan } => {
+				if let Some((sound, _)) = starting.iter_mut().find(|(sound, _)| sound.instance == instance) {
+					sound.pan = Some(pan);
+				} else if let Some((_, mut sound, ..)) = sounds.iter_mut().find(|(_, sound, ..)| sound.instance == instance) {
+					// Too late to pan one that started out unpanned.
+					if sound.pan.is_some() {
+						sound.pan = Some(pan);
+					}
+				}
+			}
+			AudioRequest::SetParameter { .. } => {}
+			AudioRequest::SetBusVolume { bus, volume } => {
+				bus_volumes.0.insert(bus, volume);
+			}
+		}
+	}
+
+	let dt = time.delta_secs();
+	for (entity, mut sound, player, sink, spatial_sink, transform) in &mut sounds {
+		// A sound that can't load never plays, so it would never despawn itself either.
+		// (The asset server has already said why.) Nor is it worth a synced group waiting for.
+		if asset_server.load_state(&player.0).is_failed() {
+			for group in sync_groups.0.values_mut() {
+				group.members.retain(|member| *member != sound.instance);
+			}
+			commands.entity(entity).despawn();
+			continue;
+		}
+		if let Some((remaining, total)) = sound.fade_out {
+			let remaining = remaining - dt;
+			if remaining <= 0. {
+				commands.entity(entity).despawn();
+				continue;
+			}
+			sound.fade_out = Some((remaining, total));
+		}
+		// The sink shows up once the asset has loaded.
+		let volume = Volume::Linear(sound.volume * sound.fade() * bus_volumes.of(sound.bus));
+		if let Some(mut sink) = sink {
+			set_sink_volume(&mut *sink, volume);
+		}
+		if let Some(mut sink) = spatial_sink {
+			set_sink_volume(&mut *sink, volume);
+		}
+		if let (Some(pan), Some(mut transform)) = (sound.pan, transform) {
+			let x = pan.clamp(-1., 1.) * EAR_GAP * 0.5;
+			if transform.translation.x != x {
+				transform.translation.x = x;
+			}
+		}
+	}
+
+	for (bevy_sound, sound) in starting {
+		let volume = bevy_sound.volume * bus_volumes.of(bevy_sound.bus);
+		let pan = bevy_sound.pan;
+		commands.spawn((
+			Name::new(format!("Sound {}", sound.path)),
+			Cleanup::<Quit>::new(),
+			AudioPlayer::new(asset_server.load(sound.path)),
+			PlaybackSettings {
+				mode: if sound.looping { PlaybackMode::Loop } else { PlaybackMode::Despawn },
+				volume: Volume::Linear(volume),
+				speed: sound.speed,
+				paused: bevy_sound.sync_group.is_some(),
+				spatial: pan.is_some(),
+				..PlaybackSettings::DESPAWN
+			},
+			Transform::from_xyz(pan.unwrap_or(0.).clamp(-1., 1.) * EAR_GAP * 0.5, 0., 0.),
+			bevy_sound,
+		));
+	}
+}
+
+fn new_sound(instance: SoundInstance, sound: &Sound) -> BevySound {
+	BevySound {
+		instance,
+		bus: sound.bus,
+		volume: sound.volume,
+		fade_out: None,
+		sync_group: None,
+		seek_to: None,
+		pan: sound.pan,
+	}
+}
+
+fn set_sink_volume(sink: &mut impl AudioSinkPlayback, volume: Volume) {
+	if sink.volume() != volume {
+		sink.set_volume(volume);
+	}
+}
+
+fn sync_bevy_sounds(
+	time: Res<Time>,
+	mut manager: ResMut<AudioManager>,
+	mut sync_groups: ResMut<BevySyncGroups>,
+	mut sounds: Query<(&mut BevySound, &mut AudioSink)>,
+	mut spatial_sounds: Query<(&mut BevySound, &SpatialAudioSink), Without<AudioSink>>,
+) {
+	sync_groups.0.retain(|_, group| !group.members.is_empty());
+
+	for (leader, group) in sync_groups.0.iter_mut() {
+		let mut members: Vec<_> = sounds.iter_mut()
+			.filter(|(sound, _)| group.members.contains(&sound.instance))
+			.collect();
+
+		if !group.started {
+			// Wait for everyone to load, then start them all from the same spot.
+			if members.len() < group.members.len() {
+				continue;
+			}
+			let start_at = members.iter().find_map(|(sound, _)| sound.seek_to).unwrap_or(0.);
+			for (sound, sink) in &mut members {
+				seek(sink, start_at);
+				sink.play();
+				sound.seek_to = None;
+			}
+			group.started = true;
+			continue;
+		}
+
+		if let Some(seconds) = members.iter().find_map(|(sound, _)| sound.seek_to) {
+			for (sound, sink) in &mut members {
+				seek(sink, seconds);
+				sound.seek_to = None;
+			}
+			continue;
+		}
+
+		group.since_correction += time.delta_secs();
+		if group.since_correction < MIN_SYNC_CORRECTION_SECONDS {
+			continue;
+		}
+		// Everyone follows the leader (or, once it has been stopped, whoever started with it first).
+		let lead = members.iter()
+			.find(|(sound, _)| sound.instance == *leader)
+			.or_else(|| members.iter().find(|(sound, _)| sound.instance == group.members[0]))
+			.map(|(sound, sink)| (sound.instance, sink.position().as_secs_f32()));
+		let Some((lead_instance, lead)) = lead else {
+			continue;
+		};
+		for (_, sink) in members.iter_mut().filter(|(sound, _)| sound.instance != lead_instance) {
+			if (sink.position().as_secs_f32() - lead).abs() > MAX_SYNC_DRIFT_SECONDS {
+				seek(sink, lead);
+				group.since_correction = 0.;
+			}
+		}
+	}
+
+	let mut positions = HashMap::new();
+	for (mut sound, sink) in &mut sounds {
+		if sound.sync_group.is_none() && let Some(seconds) = sound.seek_to.take() {
+			seek(&sink, seconds);
+		}
+		positions.insert(sound.instance, sink.position().as_secs_f32());
+	}
+	for (mut sound, sink) in &mut spatial_sounds {
+		if let Some(seconds) = sound.seek_to.take() {
+			seek(&sink, seconds);
+		}
+		positions.insert(sound.instance, sink.position().as_secs_f32());
+	}
+	manager.report_positions(positions);
+}
+
+fn seek<S: AudioSinkPlayback>(sink: &impl Deref<Target = S>, seconds: f32) {
+	if let Err(error) = sink.try_seek(Duration::from_secs_f32(seconds.max(0.))) {
+		warn!("Couldn't seek to {seconds}s: {error:?}");
+	}
+}
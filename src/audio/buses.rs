@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{Resource, Res, ResMut, Time};
+
+use super::{AudioManager, AudioBus};
+
+// =============================================================================
+// Buses: the player's volume and mute for each, and ducking under voice lines
+// =============================================================================
+
+// AudioBusSettings is what the player chose (see volume_screen.rs); AudioDucking dips one bus
+// (the music, by default) while something holds it down, e.g. a voice line being spoken.
+// apply_bus_volumes works out each bus's actual volume from both and tells the backend
+// whenever one changes.
+
+pub const AUDIO_BUSES: [AudioBus; 4] = [AudioBus::Master, AudioBus::Music, AudioBus::Sfx, AudioBus::Voice];
+
+impl AudioBus {
+	// Short lowercase name, for settings files and labels.
+	pub fn key(&self) -> &'static str {
+		match self {
+			AudioBus::Master => "master",
+			AudioBus::Music => "music",
+			AudioBus::Sfx => "sfx",
+			AudioBus::Voice => "voice",
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BusLevel {
+	pub volume: f32,		// 0 to 1.
+	pub muted: bool,
+}
+impl Default for BusLevel {
+	fn default() -> Self {
+		Self { volume: 1., muted: false }
+	}
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct AudioBusSettings(pub HashMap<AudioBus, BusLevel>);
+impl AudioBusSettings {
+	pub fn level(&self, bus: AudioBus) -> BusLevel {
+		self.0.get(&bus).copied().unwrap_or_default()
+	}
+
+	pub fn level_mut(&mut self, bus: AudioBus) -> &mut BusLevel {
+		self.0.entry(bus).or_default()
+	}
+}
+
+#[derive(Resource, Debug)]
+pub struct DuckingSettings {
+	pub bus: AudioBus,
+	pub ducked_volume: f32,		// Multiplier on the bus while fully ducked.
+	pub fade_seconds: f32,		// Into and out of the duck.
+}
+impl Default for DuckingSettings {
+	fn default() -> Self {
+		Self {
+			bus: AudioBus::Music,
+			ducked_volume: 0.35,
+			fade_seconds: 0.25,
+		}
+	}
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct AudioDucking {
+	pub hold_remaining: f32,		// Seconds left ducked.
+	pub amount: f32,				// 0 (not ducked) to 1 (fully ducked), easing toward whether it's held.
+	sent: HashMap<AudioBus, f32>,	// The volume each bus was last set to.
+}
+impl AudioDucking {
+	// Keeps the bus ducked for at least this long from now.
+	pub fn hold(&mut self, seconds: f32) {
+		self.hold_remaining = self.hold_remaining.max(seconds);
+	}
+}
+
+pub fn apply_bus_volumes(
+	time: Res<Time>,
+	settings: Res<AudioBusSettings>,
+	ducking_settings: Res<DuckingSettings>,
+	mut ducking: ResMut<AudioDucking>,
+	mut audio: ResMut<AudioManager>,
+) {
+	let dt = time.delta_secs();
+	ducking.hold_remaining = (ducking.hold_remaining - dt).max(0.);
+	let target = if ducking.hold_remaining > 0. { 1. } else { 0. };
+	let max_step = dt / ducking_settings.fade_seconds.max(f32::EPSILON);
+	ducking.amount += (target - ducking.amount).clamp(-max_step, max_step);
+
+	for bus in AUDIO_BUSES {
+		let level = settings.level(bus);
+		let mut volume = if level.muted { 0. } else { level.volume };
+		if bus == ducking_settings.bus {
+			volume *= 1. + (ducking_settings.ducked_volume - 1.) * ducking.amount;
+		}
+		// Buses start at full volume in every backend, so there's nothing to send until that changes.
+		if ducking.sent.get(&bus).copied().unwrap_or(1.) != volume {
+			audio.set_bus_volume(bus, volume);
+			ducking.sent.insert(bus, volume);
+		}
+	}
+}
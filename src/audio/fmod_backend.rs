@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use std::ffi::{CString, c_char, c_float, c_int, c_uint, c_void};
+use std::ptr;
+
+use bevy::prelude::{App, NonSendMut, ResMut, PostUpdate, error, warn};
+
+use super::{AudioManager, AudioRequest, AudioBus, SoundInstance};
+
+// =============================================================================
+// FMOD backend: FMOD Studio, through its C API
+// =============================================================================
+
+// Only built with `--features fmod`. build.rs points the linker at vendor/fmod (or FMOD_LIB_DIR), and the banks
+// exported from the FMOD project are loaded from assets/fmod. Names are mapped onto the project:
+// 	sound "audio/sfx/hitHurt.wav"	-> event:/sfx/hitHurt
+// 	AudioBus::Music					-> bus:/Music (and Master -> bus:/)
+// Fades are authored in FMOD, so Stop only chooses between fading out and cutting off.
+// Panning sets the event's own "pan" parameter (-1 to 1), so panned events need one.
+
+const FMOD_BANKS: [&str; 2] = ["assets/fmod/Master.bank", "assets/fmod/Master.strings.bank"];
+const FMOD_MAX_CHANNELS: c_int = 512;
+
+// Must match the major and minor version of the libraries in vendor/fmod.
+const FMOD_VERSION: c_uint = 0x0002_0300;
+const FMOD_OK: FmodResult = 0;
+const FMOD_INIT_NORMAL: c_uint = 0;
+const FMOD_STUDIO_INIT_NORMAL: c_uint = 0;
+const FMOD_STUDIO_LOAD_BANK_NORMAL: c_uint = 0;
+const FMOD_STUDIO_STOP_ALLOWFADEOUT: c_int = 0;
+const FMOD_STUDIO_STOP_IMMEDIATE: c_int = 1;
+const FMOD_PAN_PARAMETER: &str = "pan";
+
+type FmodResult = c_int;
+
+#[repr(C)]
+struct StudioSystem {
+	_private: [u8; 0],
+}
+#[repr(C)]
+struct Bank {
+	_private: [u8; 0],
+}
+#[repr(C)]
+struct EventDescription {
+	_private: [u8; 0],
+}
+#[repr(C)]
+struct EventInstance {
+	_private: [u8; 0],
+}
+#[repr(C)]
+struct Bus {
+	_private: [u8; 0],
+}
+
+// On Windows the FMOD import libraries carry a _vc suffix.
+#[cfg_attr(target_os = "windows", link(name = "fmodstudio_vc"))]
+#[cfg_attr(not(target_os = "windows"), link(name = "fmodstudio"))]
+unsafe extern "C" {
+	fn FMOD_Studio_System_Create(system: *mut *mut StudioSystem, header_version: c_uint) -> FmodResult;
+	fn FMOD_Studio_System_Initialize(system: *mut StudioSystem, max_channels: c_int, studio_flags: c_uint, flags: c_uint, extra_driver_data: *mut c_void) -> FmodResult;
+	fn FMOD_Studio_System_Release(system: *mut StudioSystem) -> FmodResult;
+	fn FMOD_Studio_System_Update(system: *mut StudioSystem) -> FmodResult;
+	fn FMOD_Studio_System_LoadBankFile(system: *mut StudioSystem, filename: *const c_char, flags: c_uint, bank: *mut *mut Bank) -> FmodResult;
+	fn FMOD_Studio_System_GetEvent(system: *mut StudioSystem, path: *const c_char, event: *mut *mut EventDescription) -> FmodResult;
+	fn FMOD_Studio_System_GetBus(system: *mut StudioSystem, path: *const c_char, bus: *mut *mut Bus) -> FmodResult;
+	fn FMOD_Studio_System_SetParameterByName(system: *mut StudioSystem, name: *const c_char, value: c_float, ignore_seek_speed: c_int) -> FmodResult;
+	fn FMOD_Studio_EventDescription_CreateInstance(event: *mut EventDescription, instance: *mut *mut EventInstance) -> FmodResult;
+	fn FMOD_Studio_EventInstance_Start(instance: *mut EventInstance) -> FmodResult;
+	fn FMOD_Studio_EventInstance_Stop(instance: *mut EventInstance, mode: c_int) -> FmodResult;
+	fn FMOD_Studio_EventInstance_Release(instance: *mut EventInstance) -> FmodResult;
+	fn FMOD_Studio_EventInstance_IsValid(instance: *mut EventInstance) -> c_int;
+	fn FMOD_Studio_EventInstance_SetVolume(instance: *mut EventInstance, volume: c_float) -> FmodResult;
+	fn FMOD_Studio_EventInstance_SetParameterByName(instance: *mut EventInstance, name: *const c_char, value: c_float, ignore_seek_speed: c_int) -> FmodResult;
+	fn FMOD_Studio_EventInstance_SetPitch(instance: *mut EventInstance, pitch: c_float) -> FmodResult;
+	fn FMOD_Studio_EventInstance_SetTimelinePosition(instance: *mut EventInstance, position: c_int) -> FmodResult;
+	fn FMOD_Studio_EventInstance_GetTimelinePosition(instance: *mut EventInstance, position: *mut c_int) -> FmodResult;
+	fn FMOD_Studio_Bus_SetVolume(bus: *mut Bus, volume: c_float) -> FmodResult;
+}
+
+// FMOD's handles aren't thread safe, so this lives as a NonSend resource on the main thread.
+pub struct FmodStudio {
+	system: *mut StudioSystem,
+	instances: HashMap<SoundInstance, *mut EventInstance>,
+	buses: HashMap<AudioBus, *mut Bus>,
+	sync_groups: Vec<Vec<SoundInstance>>,
+}
+impl FmodStudio {
+	fn new() -> Result<Self, String> {
+		let mut system = ptr::null_mut();
+		// SAFETY: plain FFI calls; `system` is only used once Create has succeeded.
+		unsafe {
+			check("create the studio system", FMOD_Studio_System_Create(&mut system, FMOD_VERSION))?;
+			let studio = Self { system, instances: HashMap::new(), buses: HashMap::new(), sync_groups: Vec::new() };
+			check("initialize", FMOD_Studio_System_Initialize(
+				system, FMOD_MAX_CHANNELS, FMOD_STUDIO_INIT_NORMAL, FMOD_INIT_NORMAL, ptr::null_mut(),
+			))?;
+			for path in FMOD_BANKS {
+				let mut bank = ptr::null_mut();
+				let path = c_string(path);
+				check("load a bank", FMOD_Studio_System_LoadBankFile(system, path.as_ptr(), FMOD_STUDIO_LOAD_BANK_NORMAL, &mut bank))?;
+			}
+			Ok(studio)
+		}
+	}
+
+	fn start_event(&mut self, instance: SoundInstance, path: &str, volume: f32, pitch: f32) {
+		let path = c_string(path);
+		let mut description = ptr::null_mut();
+		let mut event = ptr::null_mut();
+		// SAFETY: handles come straight from FMOD, and are checked before use.
+		unsafe {
+			if !report(path.to_str().unwrap_or_default(), FMOD_Studio_System_GetEvent(self.system, path.as_ptr(), &mut description))
+				|| !report("create instance", FMOD_Studio_EventDescription_CreateInstance(description, &mut event)) {
+				return;
+			}
+			FMOD_Studio_EventInstance_SetVolume(event, volume);
+			FMOD_Studio_EventInstance_SetPitch(event, pitch);
+			report("start", FMOD_Studio_EventInstance_Start(event));
+			// Released now, FMOD frees it once it stops; the handle stays usable until then.
+			FMOD_Studio_EventInstance_Release(event);
+		}
+		self.instances.insert(instance, event);
+	}
+
+	fn set_pan(&self, instance: SoundInstance, pan: f32) {
+		if let Some(event) = self.instances.get(&instance) {
+			let name = c_string(FMOD_PAN_PARAMETER);
+			// SAFETY: a stale handle just returns an error.
+			unsafe { report("pan", FMOD_Studio_EventInstance_SetParameterByName(*event, name.as_ptr(), pan, 0)); }
+		}
+	}
+
+	fn seek(&self, instance: SoundInstance, seconds: f32) {
+		if let Some(event) = self.instances.get(&instance) {
+			// SAFETY: a stale handle just returns an error.
+			unsafe { FMOD_Studio_EventInstance_SetTimelinePosition(*event, (seconds * 1000.) as c_int); }
+		}
+	}
+
+	fn bus(&mut self, bus: AudioBus) -> Option<*mut Bus> {
+		if let Some(handle) = self.buses.get(&bus) {
+			return Some(*handle);
+		}
+		let path = c_string(match bus {
+			AudioBus::Master => "bus:/",
+			AudioBus::Music => "bus:/Music",
+			AudioBus::Sfx => "bus:/Sfx",
+			AudioBus::Voice => "bus:/Voice",
+		});
+		let mut handle = ptr::null_mut();
+		// SAFETY: as above.
+		let found = unsafe { report(path.to_str().unwrap_or_default(), FMOD_Studio_System_GetBus(self.system, path.as_ptr(), &mut handle)) };
+		found.then(|| {
+			self.buses.insert(bus, handle);
+			handle
+		})
+	}
+}
+impl Drop for FmodStudio {
+	fn drop(&mut self) {
+		// SAFETY: releasing the system frees everything else it made.
+		unsafe {
+			FMOD_Studio_System_Release(self.system);
+		}
+	}
+}
+
+fn c_string(text: &str) -> CString {
+	CString::new(text).unwrap_or_default()
+}
+
+fn check(what: &str, result: FmodResult) -> Result<(), String> {
+	if result == FMOD_OK { Ok(()) } else { Err(format!("FMOD couldn't {what} (error {result})")) }
+}
+
+// Logs a failed call; whatever it was for is skipped, but the game carries on.
+fn report(what: &str, result: FmodResult) -> bool {
+	if let Err(message) = check(what, result) {
+		warn!("{message}");
+	}
+	result == FMOD_OK
+}
+
+// "audio/sfx/hitHurt.wav" -> "event:/sfx/hitHurt"
+fn sound_event_path(path: &str) -> String {
+	let path = path.strip_prefix("audio/").unwrap_or(path);
+	let path = path.rsplit_once('.').map_or(path, |(stem, _)| stem);
+	format!("event:/{path}")
+}
+
+pub(super) fn build(app: &mut App) {
+	match FmodStudio::new() {
+		Ok(studio) => {
+			app.insert_non_send_resource(studio)
+			.add_systems(PostUpdate, run_fmod_backend);
+		}
+		Err(message) => {
+			// No sound is better than no game.
+			error!("{message}; falling back to the null audio backend");
+			super::null_backend::build(app);
+		}
+	}
+}
+
+fn run_fmod_backend(
+	mut manager: ResMut<AudioManager>,
+	mut studio: NonSendMut<FmodStudio>,
+) {
+	for request in manager.take_requests() {
+		match request {
+			AudioRequest::PlaySound { instance, sound } => {
+				// Looping is authored on the event itself.
+				studio.start_event(instance, &sound_event_path(&sound.path), sound.volume, sound.speed);
+				if let Some(pan) = sound.pan {
+					studio.set_pan(instance, pan);
+				}
+			}
+			AudioRequest::PlaySynced { sounds, start_at } => {
+				// Events started in the same update start together in FMOD.
+				let members = sounds.iter().map(|(instance, _)| *instance).collect();
+				for (instance, sound) in sounds {
+					studio.start_event(instance, &sound_event_path(&sound.path), sound.volume, sound.speed);
+					studio.seek(instance, start_at);
+				}
+				studio.sync_groups.push(members);
+			}
+			AudioRequest::Seek { instance, seconds } => {
+				let group = studio.sync_groups.iter().find(|group| group.contains(&instance)).cloned();
+				for member in group.unwrap_or_else(|| vec![instance]) {
+					studio.seek(member, seconds);
+				}
+			}
+			AudioRequest::Stop { instance, fade_seconds } => {
+				for group in &mut studio.sync_groups {
+					group.retain(|member| *member != instance);
+				}
+				studio.sync_groups.retain(|group| !group.is_empty());
+				if let Some(event) = studio.instances.remove(&instance) {
+					let mode = if fade_seconds > 0. { FMOD_STUDIO_STOP_ALLOWFADEOUT } else { FMOD_STUDIO_STOP_IMMEDIATE };
+					// SAFETY: a stale handle just returns an error.
+					unsafe { FMOD_Studio_EventInstance_Stop(event, mode); }
+				}
+			}
+			AudioRequest::SetVolume { instance, volume } => {
+				if let Some(event) = studio.instances.get(&instance) {
+					// SAFETY: as above.
+					unsafe { FMOD_Studio_EventInstance_SetVolume(*event, volume); }
+				}
+			}
+			AudioRequest::SetPan { instance, pan } => {
+				studio.set_pan(instance, pan);
+			}
+			AudioRequest::SetParameter { name, value } => {
+				let name = c_string(&name);
+				// SAFETY: as above.
+				unsafe { report("set a parameter", FMOD_Studio_System_SetParameterByName(studio.system, name.as_ptr(), value, 0)); }
+			}
+			AudioRequest::SetBusVolume { bus, volume } => {
+				if let Some(handle) = studio.bus(bus) {
+					// SAFETY: as above.
+					unsafe { FMOD_Studio_Bus_SetVolume(handle, volume); }
+				}
+			}
+		}
+	}
+	// SAFETY: as above.
+	unsafe { report("update", FMOD_Studio_System_Update(studio.system)); }
+
+	// Every event is released as soon as it starts, so once one has finished (one-shots on their own,
+	// anything else once stopped) FMOD frees it and its handle goes invalid: stop tracking it.
+	let studio = &mut *studio;
+	// SAFETY: IsValid is made to be asked about stale handles.
+	studio.instances.retain(|_, event| unsafe { FMOD_Studio_EventInstance_IsValid(*event) } != 0);
+	for group in &mut studio.sync_groups {
+		group.retain(|member| studio.instances.contains_key(member));
+	}
+	studio.sync_groups.retain(|group| !group.is_empty());
+
+	// Positions are only worth asking for where something lines up against them (synced groups).
+	let mut positions = HashMap::new();
+	for instance in studio.sync_groups.iter().flatten() {
+		if let Some(event) = studio.instances.get(instance) {
+			let mut milliseconds = 0;
+			// SAFETY: as above.
+			if unsafe { FMOD_Studio_EventInstance_GetTimelinePosition(*event, &mut milliseconds) } == FMOD_OK {
+				positions.insert(*instance, milliseconds as f32 / 1000.);
+			}
+		}
+	}
+	manager.report_positions(positions);
+}
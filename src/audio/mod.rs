@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{App, Plugin, Resource, Update};
+
+mod bevy_backend;
+mod buses;
+pub mod null_backend;
+#[cfg(feature = "fmod")]
+mod fmod_backend;
+
+pub use buses::*;
+
+// =============================================================================
+// Audio: one API for music and sound effects, whatever ends up playing them
+// =============================================================================
+
+// Gameplay asks the AudioManager for sounds and never spawns an AudioPlayer itself.
+// Requests queue up during the frame and the backend carries them out in PostUpdate:
+// 	Bevy - bevy_audio (the default),
+// 	Null - plays nothing, but keeps every request in NullAudioLog (for headless tests),
+// 	Fmod - FMOD Studio, when built with the "fmod" cargo feature (see fmod_backend.rs).
+// The AUDIO_BACKEND environment variable ("bevy", "null" or "fmod") overrides the default.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AudioBus {
+	Master,			// Everything goes through this one as well as its own bus.
+	Music,
+	Sfx,
+	Voice,
+}
+
+// One playing sound, so it can be changed or stopped later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SoundInstance(u64);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sound {
+	pub path: String,		// Relative to assets/, e.g. "audio/sfx/hitHurt.wav".
+	pub bus: AudioBus,
+	pub volume: f32,		// Linear; 1 is as recorded.
+	pub speed: f32,			// Playback speed, which is also the pitch.
+	pub looping: bool,
+	pub pan: Option<f32>,	// -1 (left) to 1 (right). None is left as recorded, with no panning applied at all.
+}
+impl Sound {
+	pub fn new(path: impl Into<String>, bus: AudioBus) -> Self {
+		Self {
+			path: path.into(),
+			bus,
+			volume: 1.,
+			speed: 1.,
+			looping: false,
+			pan: None,
+		}
+	}
+
+	pub fn with_volume(mut self, volume: f32) -> Self {
+		self.volume = volume;
+		self
+	}
+
+	pub fn with_speed(mut self, speed: f32) -> Self {
+		self.speed = speed;
+		self
+	}
+
+	pub fn with_pan(mut self, pan: f32) -> Self {
+		self.pan = Some(pan);
+		self
+	}
+
+	pub fn looping(mut self) -> Self {
+		self.looping = true;
+		self
+	}
+}
+
+// Everything a backend can be asked to do. The null backend records these as they are.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AudioRequest {
+	PlaySound { instance: SoundInstance, sound: Sound },
+	PlaySynced { sounds: Vec<(SoundInstance, Sound)>, start_at: f32 },
+	Seek { instance: SoundInstance, seconds: f32 },
+	Stop { instance: SoundInstance, fade_seconds: f32 },
+	SetVolume { instance: SoundInstance, volume: f32 },
+	SetPan { instance: SoundInstance, pan: f32 },
+	SetParameter { name: String, value: f32 },
+	SetBusVolume { bus: AudioBus, volume: f32 },
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct AudioManager {
+	next_instance: u64,
+	requests: Vec<AudioRequest>,
+	parameters: HashMap<String, f32>,
+	positions: HashMap<SoundInstance, f32>,
+}
+impl AudioManager {
+	pub fn play_sfx(&mut self, path: &str) -> SoundInstance {
+		self.play(Sound::new(path, AudioBus::Sfx))
+	}
+
+	pub fn play(&mut self, sound: Sound) -> SoundInstance {
+		let instance = self.next_instance();
+		self.requests.push(AudioRequest::PlaySound { instance, sound });
+		instance
+	}
+
+	// Starts these together (from start_at seconds in) and keeps them lined up while they play,
+	// e.g. the stems of a piece of music. Seeking any one of them seeks them all.
+	pub fn play_synced(&mut self, sounds: Vec<Sound>, start_at: f32) -> Vec<SoundInstance> {
+		let sounds: Vec<_> = sounds.into_iter().map(|sound| (self.next_instance(), sound)).collect();
+		let instances = sounds.iter().map(|(instance, _)| *instance).collect();
+		self.requests.push(AudioRequest::PlaySynced { sounds, start_at });
+		instances
+	}
+
+	pub fn seek(&mut self, instance: SoundInstance, seconds: f32) {
+		self.requests.push(AudioRequest::Seek { instance, seconds });
+	}
+
+	pub fn stop(&mut self, instance: SoundInstance, fade_seconds: f32) {
+		self.requests.push(AudioRequest::Stop { instance, fade_seconds });
+	}
+
+	pub fn set_volume(&mut self, instance: SoundInstance, volume: f32) {
+		self.requests.push(AudioRequest::SetVolume { instance, volume });
+	}
+
+	// Only for sounds that were started with a pan (see Sound::with_pan).
+	pub fn set_pan(&mut self, instance: SoundInstance, pan: f32) {
+		self.requests.push(AudioRequest::SetPan { instance, pan });
+	}
+
+	// Parameters are named values the music and effects can react to (e.g. "fever").
+	// Setting one to the value it already has does nothing.
+	pub fn set_parameter(&mut self, name: &str, value: f32) {
+		if self.parameters.get(name) == Some(&value) {
+			return;
+		}
+		self.parameters.insert(name.to_string(), value);
+		self.requests.push(AudioRequest::SetParameter { name: name.to_string(), value });
+	}
+
+	// How far (seconds) into its track a sound is, as of the backend's last update.
+	// Not every backend can tell (the null backend never does), and sounds still loading have none yet.
+	pub fn position(&self, instance: SoundInstance) -> Option<f32> {
+		self.positions.get(&instance).copied()
+	}
+
+	pub fn set_bus_volume(&mut self, bus: AudioBus, volume: f32) {
+		self.requests.push(AudioRequest::SetBusVolume { bus, volume });
+	}
+
+	fn next_instance(&mut self) -> SoundInstance {
+		self.next_instance += 1;
+		SoundInstance(self.next_instance)
+	}
+
+	// For the backends: replaces every reported position with these.
+	fn report_positions(&mut self, positions: HashMap<SoundInstance, f32>) {
+		self.positions = positions;
+	}
+
+	// For the backends: everything asked for since last time, in order.
+	fn take_requests(&mut self) -> Vec<AudioRequest> {
+		std::mem::take(&mut self.requests)
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioBackend {
+	Bevy,
+	Null,
+	#[cfg(feature = "fmod")]
+	Fmod,
+}
+impl Default for AudioBackend {
+	fn default() -> Self {
+		match std::env::var("AUDIO_BACKEND").as_deref() {
+			Ok("bevy") => return AudioBackend::Bevy,
+			Ok("null") => return AudioBackend::Null,
+			#[cfg(feature = "fmod")]
+			Ok("fmod") => return AudioBackend::Fmod,
+			_ => {}
+		}
+		#[cfg(feature = "fmod")]
+		return AudioBackend::Fmod;
+		#[cfg(not(feature = "fmod"))]
+		return AudioBackend::Bevy;
+	}
+}
+
+#[derive(Default)]
+pub struct AudioManagerPlugin {
+	pub backend: AudioBackend,
+}
+impl Plugin for AudioManagerPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<AudioManager>()
+		.init_resource::<AudioBusSettings>()
+		.init_resource::<DuckingSettings>()
+		.init_resource::<AudioDucking>()
+		.add_systems(Update, apply_bus_volumes);
+
+		match self.backend {
+			AudioBackend::Bevy => bevy_backend::build(app),
+			AudioBackend::Null => null_backend::build(app),
+			#[cfg(feature = "fmod")]
+			AudioBackend::Fmod => fmod_backend::build(app),
+		}
+	}
+}
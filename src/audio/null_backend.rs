@@ -0,0 +1,53 @@
+use bevy::prelude::{App, Resource, ResMut, PostUpdate};
+
+use super::{AudioManager, AudioRequest};
+
+// =============================================================================
+// Null backend: silent, but remembers everything it was asked to do
+// =============================================================================
+
+// Every request, in the order it was made. Clear it whenever convenient.
+#[derive(Resource, Debug, Default)]
+pub struct NullAudioLog(pub Vec<AudioRequest>);
+
+pub(super) fn build(app: &mut App) {
+	app.init_resource::<NullAudioLog>()
+	.add_systems(PostUpdate, run_null_audio_backend);
+}
+
+fn run_null_audio_backend(
+	mut manager: ResMut<AudioManager>,
+	mut log: ResMut<NullAudioLog>,
+) {
+	log.0.extend(manager.take_requests());
+}
+
+#[cfg(test)]
+mod tests {
+	use bevy::prelude::{App, MinimalPlugins};
+
+	use super::NullAudioLog;
+	use crate::audio::{AudioManagerPlugin, AudioBackend, AudioManager, AudioRequest, AudioBus, Sound};
+
+	#[test]
+	fn logs_requests_in_order() {
+		let mut app = App::new();
+		app.add_plugins((MinimalPlugins, AudioManagerPlugin { backend: AudioBackend::Null }));
+
+		let mut audio = app.world_mut().resource_mut::<AudioManager>();
+		let hurt = audio.play_sfx("audio/sfx/hitHurt.wav");
+		audio.set_parameter("fever", 2.);
+		audio.set_parameter("fever", 2.);		// Unchanged, so not sent again.
+		audio.stop(hurt, 0.5);
+		app.update();
+
+		let log = &app.world().resource::<NullAudioLog>().0;
+		assert_eq!(log, &vec![
+			AudioRequest::PlaySound { instance: hurt, sound: Sound::new("audio/sfx/hitHurt.wav", AudioBus::Sfx) },
+			AudioRequest::SetParameter { name: String::from("fever"), value: 2. },
+			AudioRequest::Stop { instance: hurt, fade_seconds: 0.5 },
+		]);
+		// The null backend never knows where anything is.
+		assert_eq!(app.world().resource::<AudioManager>().position(hurt), None);
+	}
+}
@@ -0,0 +1,166 @@
+use bevy::prelude::{
+	Resource, Event,
+	Assets, Res, ResMut,
+	Commands,
+};
+
+use crate::music::{MusicLayers, MusicBundles, MusicBundle};
+
+// =============================================================================
+// Beat clock: the rhythm of whatever music is playing
+// =============================================================================
+
+// Counts beats and bars from the playing music bundle's BPM and how far into it the music is
+// (see MusicLayers::position), and triggers Beat and Bar as each one starts. Anything that wants
+// to keep time (flicker, bouncing keys...) observes those rather than doing its own sums.
+// With `manual` set, the music is ignored and the clock only moves via advance,
+// e.g. to drive it from a test or the sandbox.
+
+// Triggered at the start of every beat.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Beat {
+	pub index: u64,			// Beats since the top of the music.
+	pub in_bar: u32,		// 0 is the downbeat.
+}
+
+// Triggered at the start of every bar (after that bar's first Beat).
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bar {
+	pub index: u64,
+}
+
+#[derive(Resource, Debug)]
+pub struct BeatClock {
+	pub bpm: f32,
+	pub beats_per_bar: u32,
+	pub position: f32,			// Seconds into the music.
+	pub manual: bool,			// Ignore the music; only advance moves it.
+	last_beat: Option<u64>,		// The beat the last Beat was triggered for.
+}
+impl Default for BeatClock {
+	fn default() -> Self {
+		Self {
+			bpm: 120.,
+			beats_per_bar: 4,
+			position: 0.,
+			manual: false,
+			last_beat: None,
+		}
+	}
+}
+impl BeatClock {
+	pub fn beat_seconds(&self) -> f32 {
+		60. / self.bpm.max(1.)
+	}
+
+	// Whole beats since the top.
+	pub fn beat(&self) -> u64 {
+		(self.position.max(0.) / self.beat_seconds()) as u64
+	}
+
+	pub fn bar(&self) -> u64 {
+		self.beat() / self.beats_per_bar.max(1) as u64
+	}
+
+	pub fn beat_in_bar(&self) -> u32 {
+		(self.beat() % self.beats_per_bar.max(1) as u64) as u32
+	}
+
+	pub fn advance(&mut self, seconds: f32) {
+		self.position += seconds;
+	}
+}
+
+// Follows the music (unless manual), and triggers Beat/Bar whenever a new beat has started.
+// A jump (a seek, a loop back to the top, a long frame) triggers only the beat it lands in.
+pub fn tick_beat_clock(
+	mut commands: Commands,
+	layers: Res<MusicLayers>,
+	bundles: Res<MusicBundles>,
+	bundle_assets: Res<Assets<MusicBundle>>,
+	mut clock: ResMut<BeatClock>,
+) {
+	if !clock.manual {
+		let bundle = layers.bundle.as_ref()
+			.and_then(|name| bundles.0.get(name))
+			.and_then(|handle| bundle_assets.get(handle));
+		let Some(bundle) = bundle else {
+			// No music, no beat.
+			clock.last_beat = None;
+			return;
+		};
+		clock.bpm = bundle.bpm;
+		clock.beats_per_bar = bundle.beats_per_bar;
+		clock.position = layers.position;
+	}
+
+	let beat = clock.beat();
+	if clock.last_beat == Some(beat) {
+		return;
+	}
+	clock.last_beat = Some(beat);
+
+	let in_bar = clock.beat_in_bar();
+	commands.trigger(Beat { index: beat, in_bar });
+	if in_bar == 0 {
+		commands.trigger(Bar { index: clock.bar() });
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use bevy::prelude::{App, Assets, On, ResMut, Resource, Update};
+
+	use super::*;
+
+	// Every Beat and Bar triggered, in order.
+	#[derive(Resource, Debug, Default)]
+	struct Heard {
+		beats: Vec<Beat>,
+		bars: Vec<Bar>,
+	}
+
+	fn step(app: &mut App, seconds: f32) -> Heard {
+		app.world_mut().resource_mut::<BeatClock>().advance(seconds);
+		app.update();
+		std::mem::take(&mut *app.world_mut().resource_mut::<Heard>())
+	}
+
+	#[test]
+	fn manual_clock_triggers_beats_and_bars() {
+		let mut app = App::new();
+		app.init_resource::<MusicLayers>()
+		.init_resource::<MusicBundles>()
+		.init_resource::<Assets<MusicBundle>>()
+		// Half a second a beat, two beats a bar.
+		.insert_resource(BeatClock { bpm: 120., beats_per_bar: 2, manual: true, ..BeatClock::default() })
+		.init_resource::<Heard>()
+		.add_observer(|beat: On<Beat>, mut heard: ResMut<Heard>| heard.beats.push(*beat.event()))
+		.add_observer(|bar: On<Bar>, mut heard: ResMut<Heard>| heard.bars.push(*bar.event()))
+		.add_systems(Update, tick_beat_clock);
+
+		// The top of the music is the first downbeat.
+		let heard = step(&mut app, 0.);
+		assert_eq!(heard.beats, vec![Beat { index: 0, in_bar: 0 }]);
+		assert_eq!(heard.bars, vec![Bar { index: 0 }]);
+
+		// Part way through a beat: nothing new.
+		let heard = step(&mut app, 0.25);
+		assert!(heard.beats.is_empty() && heard.bars.is_empty(), "{heard:?}");
+
+		// The offbeat: a beat, but no bar.
+		let heard = step(&mut app, 0.25);
+		assert_eq!(heard.beats, vec![Beat { index: 1, in_bar: 1 }]);
+		assert!(heard.bars.is_empty(), "{heard:?}");
+
+		// The next downbeat.
+		let heard = step(&mut app, 0.5);
+		assert_eq!(heard.beats, vec![Beat { index: 2, in_bar: 0 }]);
+		assert_eq!(heard.bars, vec![Bar { index: 1 }]);
+
+		// A jump of several beats only triggers the one it lands in.
+		let heard = step(&mut app, 2.);
+		assert_eq!(heard.beats, vec![Beat { index: 6, in_bar: 0 }]);
+		assert_eq!(heard.bars, vec![Bar { index: 3 }]);
+	}
+}
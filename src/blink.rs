@@ -0,0 +1,248 @@
+use bevy::prelude::{
+	Component, Bundle, Resource, Event, On, In,
+	Name,
+	Query, Res, ResMut,
+	Commands,
+	ButtonInput, KeyCode, MouseButton,
+	Time, Transform, Visibility,
+	Color, Vec2, Vec3,
+};
+use bevy::ecs::system::SystemId;
+use bevy_vector_shapes::prelude::*;
+use rand::Rng;
+
+use crate::{VIRTUAL_RESOLUTION, FeverLevel};
+use crate::cleanup::{Cleanup, Quit};
+use crate::draft::Draft;
+use crate::draft_corruption::corrupt_draft_on_blink;
+use crate::ghost_slide::reset_ghost_on_blink;
+use crate::keyboard::restore_legends_on_blink;
+
+// =============================================================================
+// Blinking
+// =============================================================================
+
+// The player can hold BLINK_KEY (or the right mouse button) to close their eyes, and the fever
+// makes them blink on their own. Effects decide what a blink does to them by registering a
+// reaction system (see register_blink_reactions), which runs the moment the eyes are fully shut.
+
+pub const BLINK_KEY: KeyCode = KeyCode::Tab;
+pub const BLINK_BUTTON: MouseButton = MouseButton::Right;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlinkKind {
+	Voluntary,
+	Involuntary,
+}
+
+// Trigger to make the player blink whether they like it or not.
+#[derive(Event, Debug)]
+pub struct InvoluntaryBlink;
+
+#[derive(Clone, Copy, Debug)]
+pub struct BlinkStage {
+	pub involuntary_interval: Option<(f32, f32)>,		// Seconds between involuntary blinks (min, max); None for never.
+	pub clears_draft: bool,								// Whether an involuntary blink wipes the typed draft.
+}
+
+// One entry per FeverLevel; levels past the end use the last entry.
+#[derive(Resource, Debug)]
+pub struct BlinkSettings {
+	pub close_seconds: f32,
+	pub open_seconds: f32,
+	pub involuntary_hold_seconds: f32,		// How long an involuntary blink keeps the eyes shut.
+	pub stages: Vec<BlinkStage>,
+}
+impl Default for BlinkSettings {
+	fn default() -> Self {
+		Self {
+			close_seconds: 0.12,
+			open_seconds: 0.18,
+			involuntary_hold_seconds: 0.25,
+			stages: vec![
+				BlinkStage { involuntary_interval: None, clears_draft: false },
+				BlinkStage { involuntary_interval: None, clears_draft: false },
+				BlinkStage { involuntary_interval: Some((12., 20.)), clears_draft: false },
+				BlinkStage { involuntary_interval: Some((8., 14.)), clears_draft: true },
+			],
+		}
+	}
+}
+impl BlinkSettings {
+	pub fn stage(&self, fever_level: usize) -> BlinkStage {
+		self.stages.get(fever_level)
+			.or(self.stages.last())
+			.copied()
+			.unwrap_or(BlinkStage { involuntary_interval: None, clears_draft: false })
+	}
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct BlinkState {
+	pub closedness: f32,						// 0 is wide open, 1 is shut.
+	pub holding: bool,							// Player is holding their eyes shut.
+	pub involuntary_hold: Option<f32>,			// Seconds left on an involuntary blink, once shut.
+	pub pending: Option<BlinkKind>,				// What kind of blink is closing the eyes right now.
+	pub until_involuntary: Option<f32>,			// Countdown to the next fever blink.
+}
+
+// Reaction systems to run when the eyes close, registered once at startup.
+#[derive(Resource, Default)]
+pub struct BlinkReactions(pub Vec<SystemId<In<BlinkKind>>>);
+
+pub fn register_blink_reactions(mut commands: Commands) {
+	let reactions = vec![
+		commands.register_system(reset_ghost_on_blink),
+		commands.register_system(restore_legends_on_blink),
+		commands.register_system(clear_draft_on_blink),
+		commands.register_system(corrupt_draft_on_blink),
+	];
+	commands.insert_resource(BlinkReactions(reactions));
+}
+
+// Eyelids are drawn over everything but the power cycle overlay.
+const EYELID_Z: f32 = 40.;
+const EYELID_COLOR: Color = Color::srgb(0.09, 0.04, 0.04);
+
+#[derive(Component)]
+pub struct Eyelid {
+	pub upper: bool,
+}
+
+fn eyelid_shape() -> impl Bundle {
+	ShapeBundle::rect(
+		&ShapeConfig {
+			color: EYELID_COLOR,
+			transform: Transform::from_scale(Vec3::ZERO),
+			..ShapeConfig::default_2d()
+		},
+		Vec2::ONE,
+	)
+}
+
+pub fn spawn_eyelids(mut commands: Commands) {
+	commands.spawn((
+		Name::new("Eyelids"),
+		Cleanup::<Quit>::new(),
+		Transform::from_xyz(0., 0., EYELID_Z),
+		Visibility::default(),
+	)).with_children(|parent| {
+		for upper in [true, false] {
+			parent.spawn((Name::new("Eyelid"), Eyelid { upper }, eyelid_shape()));
+		}
+	});
+}
+
+pub fn on_involuntary_blink(
+	_event: On<InvoluntaryBlink>,
+	mut blink_state: ResMut<BlinkState>,
+) {
+	if blink_state.pending.is_none() {
+		blink_state.pending = Some(BlinkKind::Involuntary);
+	}
+}
+
+pub fn read_blink_input(
+	keyboard_input: Res<ButtonInput<KeyCode>>,
+	mouse_input: Res<ButtonInput<MouseButton>>,
+	mut blink_state: ResMut<BlinkState>,
+) {
+	let holding = keyboard_input.pressed(BLINK_KEY) || mouse_input.pressed(BLINK_BUTTON);
+	if holding && blink_state.pending.is_none() {
+		blink_state.pending = Some(BlinkKind::Voluntary);
+	}
+	blink_state.holding = holding;
+}
+
+// Count down to the next fever blink, if the current stage has them.
+pub fn schedule_involuntary_blinks(
+	mut commands: Commands,
+	time: Res<Time>,
+	fever_level: Res<FeverLevel>,
+	settings: Res<BlinkSettings>,
+	mut blink_state: ResMut<BlinkState>,
+) {
+	let Some((min, max)) = settings.stage(fever_level.0).involuntary_interval else {
+		blink_state.until_involuntary = None;
+		return;
+	};
+
+	let remaining = blink_state.until_involuntary
+		.unwrap_or_else(|| rand::rng().random_range(min..=max))
+		- time.delta_secs();
+
+	if remaining <= 0. {
+		commands.trigger(InvoluntaryBlink);
+		blink_state.until_involuntary = None;
+	} else {
+		blink_state.until_involuntary = Some(remaining);
+	}
+}
+
+pub fn animate_blink(
+	mut commands: Commands,
+	time: Res<Time>,
+	settings: Res<BlinkSettings>,
+	reactions: Res<BlinkReactions>,
+	mut blink_state: ResMut<BlinkState>,
+	mut eyelids: Query<(&Eyelid, &mut Transform)>,
+) {
+	let dt = time.delta_secs();
+	let was_shut = blink_state.closedness >= 1.;
+
+	let closing = match blink_state.pending {
+		Some(BlinkKind::Voluntary) => blink_state.holding,
+		Some(BlinkKind::Involuntary) => blink_state.involuntary_hold.is_none_or(|hold| hold > 0.),
+		None => false,
+	};
+
+	if closing {
+		blink_state.closedness = (blink_state.closedness + dt / settings.close_seconds).min(1.);
+	} else {
+		blink_state.closedness = (blink_state.closedness - dt / settings.open_seconds).max(0.);
+	}
+
+	let shut = blink_state.closedness >= 1.;
+	if shut && !was_shut {
+		// Eyes just closed: let every registered effect react.
+		if let Some(kind) = blink_state.pending {
+			for reaction in &reactions.0 {
+				commands.run_system_with(*reaction, kind);
+			}
+			if kind == BlinkKind::Involuntary {
+				blink_state.involuntary_hold = Some(settings.involuntary_hold_seconds);
+			}
+		}
+	}
+	if shut && let Some(hold) = blink_state.involuntary_hold.as_mut() {
+		*hold -= dt;
+	}
+	if blink_state.closedness <= 0. && !closing {
+		blink_state.pending = None;
+		blink_state.involuntary_hold = None;
+	}
+
+	// Lids meet in the middle; ease so they linger a little near open and shut.
+	let screen = VIRTUAL_RESOLUTION.as_vec2();
+	let eased = blink_state.closedness * blink_state.closedness * (3. - 2. * blink_state.closedness);
+	let lid_height = screen.y * 0.5 * eased;
+	for (eyelid, mut transform) in &mut eyelids {
+		let edge = if eyelid.upper { screen.y * 0.5 } else { -screen.y * 0.5 };
+		let toward_center = if eyelid.upper { -1. } else { 1. };
+		transform.translation.y = edge + toward_center * lid_height * 0.5;
+		transform.scale = Vec3::new(screen.x, lid_height, 1.);
+	}
+}
+
+// Blink reaction: a fever blink can wipe out typing progress.
+pub fn clear_draft_on_blink(
+	In(kind): In<BlinkKind>,
+	fever_level: Res<FeverLevel>,
+	settings: Res<BlinkSettings>,
+	mut draft: ResMut<Draft>,
+) {
+	if kind == BlinkKind::Involuntary && settings.stage(fever_level.0).clears_draft && !draft.0.is_empty() {
+		draft.0.clear();
+	}
+}
+
@@ -0,0 +1,166 @@
+use bevy::prelude::{
+	Component, Event, On,
+	Entity,
+	Query, Res, With, Without,
+	Commands,
+	Time, Transform,
+	Color, Vec2,
+};
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::noise_utils::*;
+use crate::physics::*;
+use crate::sent_message::{MsgText, BubbleTint};
+
+// =============================================================================
+// Bubble disorder: sent messages falling, sliding, spinning and changing color
+// =============================================================================
+
+// Disordered bubbles leave the layout: they remember their slot and are moved by the physics
+// (or just recolored) until ReturnBubbles tweens them back into place.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BubbleMotion {
+	Fall,			// Drops off the bottom of the screen.
+	Slide,			// Skids sideways and comes to rest (possibly half off screen).
+	Spin,			// Stays put, slowly turning.
+	ColorDrift,		// Stays put; its color wanders off.
+}
+
+// Trigger to knock this many (random) bubbles out of the layout.
+#[derive(Event, Debug)]
+pub struct DisorderBubbles {
+	pub count: usize,
+	pub motion: BubbleMotion,
+}
+
+// Trigger to put every disordered bubble back where it belongs.
+#[derive(Event, Debug)]
+pub struct ReturnBubbles;
+
+// A bubble that has left the layout, and the slot it came from.
+#[derive(Component, Debug)]
+pub struct DisorderedBubble {
+	pub slot: Transform,
+}
+
+#[derive(Component, Debug)]
+pub struct BubbleColorDrift {
+	pub seed: f32,
+}
+
+// A bubble on its way back to its slot (and its own color).
+#[derive(Component, Debug)]
+pub struct ReturningBubble {
+	pub from: Transform,
+	pub from_tint: f32,
+	pub elapsed: f32,
+}
+
+const BUBBLE_RETURN_SECONDS: f32 = 0.6;
+// Far enough below the screen that no part of a falling bubble can still be showing.
+const BUBBLE_FALLEN_MARGIN: f32 = 400.;
+const BUBBLE_COLOR_DRIFT_AMOUNT: f32 = 0.7;
+const BUBBLE_COLOR_DRIFT_SPEED: f32 = 0.25;
+
+pub fn on_disorder_bubbles(
+	event: On<DisorderBubbles>,
+	mut commands: Commands,
+	bubbles: Query<(Entity, &Transform), (With<MsgText>, Without<DisorderedBubble>)>,
+) {
+	let mut rng = rand::rng();
+	let in_layout: Vec<_> = bubbles.iter().collect();
+	for (entity, transform) in in_layout.choose_multiple(&mut rng, event.count) {
+		let mut bubble = commands.entity(*entity);
+		bubble.insert(DisorderedBubble { slot: **transform });
+
+		match event.motion {
+			BubbleMotion::Fall => {
+				let velocity = Vec2::new(rng.random_range(-80. ..80.), rng.random_range(0. ..150.));
+				bubble.insert((
+					PhysicsBodyBundle::from_transform(transform, velocity, rng.random_range(-1. ..1.)),
+					Gravity::default(),
+				));
+			}
+			BubbleMotion::Slide => {
+				let direction = if rng.random_bool(0.5) { 1. } else { -1. };
+				let velocity = Vec2::new(direction * rng.random_range(300. ..650.), 0.);
+				bubble.insert((
+					PhysicsBodyBundle::from_transform(transform, velocity, direction * rng.random_range(0. ..0.3)),
+					Damping { linear: 1.2, angular: 1.2 },
+				));
+			}
+			BubbleMotion::Spin => {
+				let spin = rng.random_range(0.6..1.5) * if rng.random_bool(0.5) { 1. } else { -1. };
+				bubble.insert(PhysicsBodyBundle::from_transform(transform, Vec2::ZERO, spin));
+			}
+			BubbleMotion::ColorDrift => {
+				bubble.insert((
+					BubbleColorDrift { seed: rng.random_range(0. ..100.) },
+					BubbleTint::default(),
+				));
+			}
+		}
+	}
+}
+
+pub fn drift_bubble_colors(
+	time: Res<Time>,
+	mut bubbles: Query<(&BubbleColorDrift, &mut BubbleTint)>,
+) {
+	let t = time.elapsed_secs() * BUBBLE_COLOR_DRIFT_SPEED;
+	for (drift, mut tint) in &mut bubbles {
+		let hue = (smooth_noise(t, drift.seed) + 1.) * 180.;
+		tint.color = Color::hsl(hue, 0.65, 0.55);
+		// Ease in from the bubble's own color rather than jumping.
+		tint.amount = (tint.amount + time.delta_secs()).min(BUBBLE_COLOR_DRIFT_AMOUNT);
+	}
+}
+
+pub fn on_return_bubbles(
+	_event: On<ReturnBubbles>,
+	mut commands: Commands,
+	bubbles: Query<(Entity, &Transform, Option<&BubbleTint>), (With<DisorderedBubble>, Without<ReturningBubble>)>,
+) {
+	for (entity, transform, tint) in &bubbles {
+		commands.entity(entity)
+			.remove::<(PhysicsBodyBundle, Gravity, Damping, BubbleColorDrift)>()
+			.insert(ReturningBubble { from: *transform, from_tint: tint.map_or(0., |tint| tint.amount), elapsed: 0. });
+	}
+}
+
+pub fn return_bubbles(
+	mut commands: Commands,
+	time: Res<Time>,
+	mut bubbles: Query<(Entity, &DisorderedBubble, &mut ReturningBubble, &mut Transform, Option<&mut BubbleTint>)>,
+) {
+	for (entity, disordered, mut returning, mut transform, tint) in &mut bubbles {
+		returning.elapsed += time.delta_secs();
+		let t = (returning.elapsed / BUBBLE_RETURN_SECONDS).min(1.);
+		let eased = t * t * (3. - 2. * t);
+
+		transform.translation = returning.from.translation.lerp(disordered.slot.translation, eased);
+		transform.rotation = returning.from.rotation.slerp(disordered.slot.rotation, eased);
+		transform.scale = returning.from.scale.lerp(disordered.slot.scale, eased);
+		if let Some(mut tint) = tint {
+			tint.amount = returning.from_tint * (1. - eased);
+		}
+
+		if t >= 1. {
+			commands.entity(entity).remove::<(DisorderedBubble, ReturningBubble)>();
+		}
+	}
+}
+
+// A fallen bubble is off the bottom of the screen for good (until it's returned): stop simulating it.
+pub fn stop_fallen_bubbles(
+	mut commands: Commands,
+	bounds: Res<PhysicsBounds>,
+	bubbles: Query<(Entity, &PhysicsPosition), (With<DisorderedBubble>, With<Gravity>)>,
+) {
+	for (entity, position) in &bubbles {
+		if position.translation.y < bounds.screen.min.y - BUBBLE_FALLEN_MARGIN {
+			commands.entity(entity).remove::<(PhysicsBodyBundle, Gravity)>();
+		}
+	}
+}
@@ -5,11 +5,14 @@ use bevy::{
 		Resource,
 		Query, With,
 		Commands,
+		MessageReader, StateTransitionEvent,
+		App, Plugin, IntoScheduleConfigs, StateTransition,
 	},
+	state::state::StateTransitionSystems,
 	ecs::system::SystemId,
 };
 
-use crate::app_state::AppStateTrait;
+use crate::app_state::{AppState, AppStateTrait, add_state_cleanup_systems};
 
 // Cleanup marker generic as alluded to in Bevy best practices:
 // https://github.com/tbillington/bevy_best_practices
@@ -44,7 +47,12 @@ pub struct Quit;
 // as Bevy will despawn the whole World on exit, but there may be tasks associated with such entities.
 // Or rather, with the act of exiting generally. (See quit_cleanup_system below.)
 
-// Example:
+// Likewise, entities that only make sense until the computer next turns off (see power_cycle.rs)
+// can be marked with Cleanup<PowerCycle>; they are despawned while the screen is dark.
+#[derive(Debug, Eq, PartialEq)]
+pub struct PowerCycle;
+
+// Example (CleanupPlugin does this for every AppState):
 // app.add_systems(OnExit(AppState::Splash), cleanup_system::<Cleanup<Splash>>);
 
 // Notice that only components with our app state marker trait can be passed in to this generic. 
@@ -78,4 +86,91 @@ pub struct QuitCleanupSystemId(pub SystemId);
 pub fn register_quit_cleanup_system(mut commands: Commands) {
     let system_id = commands.register_system(quit_cleanup_system);
     commands.insert_resource(QuitCleanupSystemId(system_id));
-}
\ No newline at end of file
+}
+
+// A Cleanup<T> dies on leaving its one state. For something that belongs to several, LivesIn lists
+// them all instead: it survives moving between those states, and is despawned as soon as any other
+// state is entered. The usual case is the game and its pause menu - the room stays put behind the
+// pause overlay, and only goes once the player quits to the menu (or wins).
+#[derive(Component, Debug)]
+pub struct LivesIn(pub Vec<AppState>);
+impl LivesIn {
+	// The room: there for the game and while it's paused.
+	pub fn game() -> Self {
+		Self(vec![AppState::InGame, AppState::PauseMenu])
+	}
+}
+
+// Runs in the StateTransition schedule, between OnExit and OnEnter, so whatever the entered state
+// sets up in OnEnter never sees the entities that don't belong in it.
+
+pub fn cleanup_outside_states(
+	mut commands: Commands,
+	mut transitions: MessageReader<StateTransitionEvent<AppState>>,
+	query: Query<(Entity, &LivesIn)>,
+) {
+	for transition in transitions.read() {
+		let Some(entered) = transition.entered else {
+			continue;
+		};
+		if transition.exited == Some(entered) {
+			continue;
+		}
+		for (entity, lives_in) in &query {
+			if !lives_in.0.contains(&entered) {
+				commands.entity(entity).despawn();
+			}
+		}
+	}
+}
+
+// Both kinds of state cleanup, for every AppState: Cleanup<T> on exit (see app_state.rs), and LivesIn.
+pub struct CleanupPlugin;
+impl Plugin for CleanupPlugin {
+	fn build(&self, app: &mut App) {
+		add_state_cleanup_systems(app);
+		app.add_systems(StateTransition, cleanup_outside_states.in_set(StateTransitionSystems::TransitionSchedules));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use bevy::prelude::{App, MinimalPlugins, NextState, AppExtStates, OnEnter, ResMut};
+	use bevy::state::app::StatesPlugin;
+
+	use super::*;
+
+	// How many LivesIn entities were left when OnEnter(MainMenu) ran.
+	#[derive(Resource, Default)]
+	struct LeftOnEnteringMainMenu(Option<usize>);
+
+	fn go_to(app: &mut App, state: AppState) {
+		app.world_mut().resource_mut::<NextState<AppState>>().set(state);
+		app.update();
+	}
+
+	#[test]
+	fn lives_in_survives_its_states_only() {
+		let mut app = App::new();
+		app.add_plugins((MinimalPlugins, StatesPlugin, CleanupPlugin))
+		.init_state::<AppState>()
+		.init_resource::<LeftOnEnteringMainMenu>()
+		.add_systems(OnEnter(AppState::MainMenu), |query: Query<&LivesIn>, mut left: ResMut<LeftOnEnteringMainMenu>| {
+			left.0 = Some(query.iter().count());
+		});
+		go_to(&mut app, AppState::InGame);
+
+		let entity = app.world_mut().spawn(LivesIn::game()).id();
+
+		// Moving between its states, or "into" the state it's already in, leaves it be.
+		go_to(&mut app, AppState::PauseMenu);
+		go_to(&mut app, AppState::PauseMenu);
+		go_to(&mut app, AppState::InGame);
+		assert!(app.world().get_entity(entity).is_ok());
+
+		// Gone before the state it doesn't live in is even set up.
+		go_to(&mut app, AppState::MainMenu);
+		assert_eq!(app.world().resource::<LeftOnEnteringMainMenu>().0, Some(0));
+		assert!(app.world().get_entity(entity).is_err());
+	}
+}
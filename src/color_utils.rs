@@ -1,9 +1,12 @@
 use bevy::prelude::{
-	Resource, Res, ResMut,
+	Resource, Res, ResMut, DetectChanges,
 	Query, With,
+	Time,
 	Color,
 };
+use bevy::color::Mix;
 
+use crate::FeverLevel;
 use crate::sent_message::{
 	MsgText,
 	FontColor,
@@ -99,6 +102,59 @@ impl Default for ColorScheme {
 	}
 }
 
+impl ColorScheme {
+	// The scheme for a given light/dark and iOS/Android mode, as the mode toggles would set it.
+	pub fn for_modes(dark: bool, android: bool) -> Self {
+		Self {
+			top_bkg_color: if dark { DKMODE_TOP_BKG_COLOR } else { LTMODE_TOP_BKG_COLOR },
+			top_rule_color: if dark { DKMODE_TOP_RULE_COLOR } else { LTMODE_TOP_RULE_COLOR },
+			mid_bkg_color: if dark { DKMODE_MID_BKG_COLOR } else { LTMODE_MID_BKG_COLOR },
+			sys_text_color: if dark { DKMODE_SYS_TEXT_COLOR } else { LTMODE_SYS_TEXT_COLOR },
+			key_text_color: if dark { DKMODE_KEY_TEXT_COLOR } else { LTMODE_KEY_TEXT_COLOR },
+			key_color: if dark { DKMODE_KEY_COLOR } else { LTMODE_KEY_COLOR },
+			key_color_caps: if dark { DKMODE_CAPS_COLOR } else { LTMODE_CAPS_COLOR },
+			key_color_bksp: if dark { DKMODE_BKSP_COLOR } else { LTMODE_BKSP_COLOR },
+			keyboard_color: if dark { DKMODE_KEYBOARD_COLOR } else { LTMODE_KEYBOARD_COLOR },
+			my_bubble_color: if android { GREEN_BUBBLE_COLOR } else { BLUE_BUBBLE_COLOR },
+			my_text_color: DEFAULT_MY_TEXT_COLOR,
+			their_bubble_color: if dark { DKMODE_THEIR_BUBBLE_COLOR } else { LTMODE_THEIR_BUBBLE_COLOR },
+			their_text_color: if dark { DKMODE_THEIR_TEXT_COLOR } else { LTMODE_THEIR_TEXT_COLOR },
+		}
+	}
+
+	// Dark mode gone wrong: every bit of text (and every key legend) in the color of whatever it sits on.
+	pub fn illegible(android: bool) -> Self {
+		let dark = Self::for_modes(true, android);
+		Self {
+			sys_text_color: dark.mid_bkg_color,
+			key_text_color: dark.key_color,
+			key_color_caps: dark.key_color,
+			key_color_bksp: dark.key_color,
+			my_text_color: dark.my_bubble_color,
+			their_text_color: dark.their_bubble_color,
+			..dark
+		}
+	}
+
+	pub fn mix(&self, other: &Self, t: f32) -> Self {
+		Self {
+			top_bkg_color: self.top_bkg_color.mix(&other.top_bkg_color, t),
+			top_rule_color: self.top_rule_color.mix(&other.top_rule_color, t),
+			mid_bkg_color: self.mid_bkg_color.mix(&other.mid_bkg_color, t),
+			sys_text_color: self.sys_text_color.mix(&other.sys_text_color, t),
+			key_text_color: self.key_text_color.mix(&other.key_text_color, t),
+			key_color: self.key_color.mix(&other.key_color, t),
+			key_color_caps: self.key_color_caps.mix(&other.key_color_caps, t),
+			key_color_bksp: self.key_color_bksp.mix(&other.key_color_bksp, t),
+			keyboard_color: self.keyboard_color.mix(&other.keyboard_color, t),
+			my_bubble_color: self.my_bubble_color.mix(&other.my_bubble_color, t),
+			my_text_color: self.my_text_color.mix(&other.my_text_color, t),
+			their_bubble_color: self.their_bubble_color.mix(&other.their_bubble_color, t),
+			their_text_color: self.their_text_color.mix(&other.their_text_color, t),
+		}
+	}
+}
+
 // This resource tracks the currently selected color mode (i.e. light, dark).
 #[derive(Resource)]
 pub struct DarkModeEnabled(pub bool);	// Can we set up an observer for when this changes?
@@ -148,6 +204,65 @@ pub fn on_android_mode_enabled_changed(
 	color_scheme.my_bubble_color = if android_mode_enabled.0 { GREEN_BUBBLE_COLOR } else { BLUE_BUBBLE_COLOR };
 }
 
+// =============================================================================
+// Illegible color scheme (fever effect)
+// =============================================================================
+
+// While active, the ColorScheme fades over to ColorScheme::illegible; once it isn't, it fades back
+// to whatever the dark/Android mode toggles say. It is active during the fever stages listed in
+// its settings, or whenever forced on.
+#[derive(Resource, Debug)]
+pub struct IllegibleScheme {
+	pub forced: bool,
+	pub fever_levels: Vec<usize>,
+	pub fade_seconds: f32,
+	pub blend: f32,					// 0 is the normal scheme, 1 is fully illegible.
+}
+impl Default for IllegibleScheme {
+	fn default() -> Self {
+		Self {
+			forced: false,
+			fever_levels: vec![3],
+			fade_seconds: 4.,
+			blend: 0.,
+		}
+	}
+}
+
+// Runs after the mode toggles, so a toggle mid-effect just changes what the scheme fades back to.
+pub fn apply_illegible_scheme(
+	time: Res<Time>,
+	fever_level: Res<FeverLevel>,
+	dark_mode_enabled: Res<DarkModeEnabled>,
+	android_mode_enabled: Res<AndroidModeEnabled>,
+	mut illegible_scheme: ResMut<IllegibleScheme>,
+	mut color_scheme: ResMut<ColorScheme>,
+) {
+	let active = illegible_scheme.forced || illegible_scheme.fever_levels.contains(&fever_level.0);
+	if !active && illegible_scheme.blend <= 0. {
+		return;
+	}
+
+	let step = time.delta_secs() / illegible_scheme.fade_seconds.max(f32::EPSILON);
+	let blend = if active { illegible_scheme.blend + step } else { illegible_scheme.blend - step };
+	let blend = blend.clamp(0., 1.);
+	// Fully faded in and the modes haven't changed: nothing to redo (and nothing to recolor).
+	if blend == illegible_scheme.blend && !dark_mode_enabled.is_changed() && !android_mode_enabled.is_changed() {
+		return;
+	}
+	illegible_scheme.blend = blend;
+
+	let base = ColorScheme::for_modes(dark_mode_enabled.0, android_mode_enabled.0);
+	let target = ColorScheme::illegible(android_mode_enabled.0);
+	// Ease so the text seems to hold on for a while, then sink into the background.
+	let t = blend * blend * (3. - 2. * blend);
+	let scheme = base.mix(&target, t);
+	// Only write when the blend actually changes the colors, so the recolor systems don't run for nothing.
+	if *color_scheme != scheme {
+		*color_scheme = scheme;
+	}
+}
+
 // This runs when ColorScheme changes (see App setup).
 pub fn update_colors_on_color_scheme_change(
 	// For now, the only entities with Text are those created via SentMessageBundle.
@@ -170,4 +285,36 @@ pub fn print_messages_on_color_scheme_change(
 		// println!("\nText: {}\nFontColor: {:?}\nBkgColor: {:?}\nSide: {:?}\nIndex: {}", text.0, font_color.0, bkg_color.0, side.0, index.0);
 		print_sent_message(Some(text), Some(font_color), Some(bkg_color), Some(is_mine), Some(side), Some(index));
 	}
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn illegible_text_matches_what_it_sits_on() {
+		for android in [false, true] {
+			let dark = ColorScheme::for_modes(true, android);
+			let illegible = ColorScheme::illegible(android);
+			assert_eq!(illegible.sys_text_color, dark.mid_bkg_color);
+			assert_eq!(illegible.key_text_color, dark.key_color);
+			assert_eq!(illegible.my_text_color, dark.my_bubble_color);
+			assert_eq!(illegible.their_text_color, dark.their_bubble_color);
+			// The backgrounds themselves are dark mode's.
+			assert_eq!(illegible.mid_bkg_color, dark.mid_bkg_color);
+			assert_eq!(illegible.my_bubble_color, dark.my_bubble_color);
+			assert_eq!(illegible.keyboard_color, dark.keyboard_color);
+		}
+	}
+
+	#[test]
+	fn mix_runs_from_one_scheme_to_the_other() {
+		let light = ColorScheme::for_modes(false, false);
+		let dark = ColorScheme::for_modes(true, false);
+		assert!(light.mix(&dark, 0.) == light);
+		assert!(light.mix(&dark, 1.) == dark);
+
+		let halfway = light.mix(&dark, 0.5);
+		assert_eq!(halfway.mid_bkg_color, light.mid_bkg_color.mix(&dark.mid_bkg_color, 0.5));
+		assert_eq!(halfway.my_text_color, light.my_text_color.mix(&dark.my_text_color, 0.5));
+	}
+}
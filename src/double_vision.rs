@@ -0,0 +1,251 @@
+use bevy::prelude::{
+	Component, Resource,
+	Name,
+	Query, Res, ResMut, With, Changed,
+	Commands,
+	Assets, Handle, Image, Mesh, Mesh2d, Rectangle,
+	Camera, Camera2d, ClearColorConfig, Msaa,
+	Time, Transform,
+	Color, Vec2,
+};
+use bevy::asset::Asset;
+use bevy::camera::{RenderTarget, visibility::RenderLayers};
+use bevy::reflect::TypePath;
+use bevy::render::render_resource::{AsBindGroup, ShaderType, TextureFormat};
+use bevy::shader::ShaderRef;
+use bevy::sprite_render::{Material2d, AlphaMode2d, MeshMaterial2d};
+
+use crate::{VIRTUAL_RESOLUTION, FeverLevel};
+use crate::cleanup::{Cleanup, Quit};
+
+// =============================================================================
+// Double vision: the keyboard and ghost text wobbling, doubling up and blurring
+// =============================================================================
+
+// Each distorted render layer is drawn by its own camera into an offscreen image, and that image is
+// put back on screen by a full-screen quad with a DoubleVisionMaterial. Everything left on the
+// default layer (bubbles, cracks, eyelids...) never goes through the shader, so it stays crisp.
+// To distort something, put it on one of the layers below - with Propagate, so its shapes come along.
+
+pub const KEYBOARD_RENDER_LAYER: usize = 1;
+pub const GHOST_TEXT_RENDER_LAYER: usize = 2;
+
+pub const DOUBLE_VISION_SHADER_PATH: &str = "shaders/double_vision.wgsl";
+
+// How a distorted layer is currently drawn. Lives on the layer's composite quad:
+// set it directly, or add DoubleVisionFromFever and let the FeverLevel decide the strength.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct DoubleVision {
+	pub strength: f32,				// 0 is crisp; everything below is scaled by this.
+	pub offset: Vec2,				// Pixels between the two images.
+	pub wobble_amplitude: f32,		// Pixels.
+	pub wobble_frequency: f32,		// Hz.
+	pub wobble_wavelength: f32,		// Pixels, top to bottom.
+	pub blur: f32,					// Pixels.
+}
+impl Default for DoubleVision {
+	fn default() -> Self {
+		Self {
+			strength: 0.,
+			offset: Vec2::new(14., -6.),
+			wobble_amplitude: 6.,
+			wobble_frequency: 0.7,
+			wobble_wavelength: 240.,
+			blur: 2.5,
+		}
+	}
+}
+
+// The composite quad for this render layer.
+#[derive(Component, Debug)]
+pub struct DistortedLayer(pub usize);
+
+// The layer's strength eases towards whatever DoubleVisionSettings says for the current FeverLevel.
+#[derive(Component, Debug)]
+pub struct DoubleVisionFromFever;
+
+// Renders one distorted layer offscreen (see above).
+#[derive(Component, Debug)]
+pub struct LayerCamera;
+
+#[derive(Clone, Copy, Debug)]
+pub struct DoubleVisionStage {
+	pub keyboard: f32,
+	pub ghost_text: f32,
+}
+impl DoubleVisionStage {
+	const CALM: Self = Self {
+		keyboard: 0.,
+		ghost_text: 0.,
+	};
+
+	pub fn strength(&self, layer: usize) -> f32 {
+		match layer {
+			KEYBOARD_RENDER_LAYER => self.keyboard,
+			GHOST_TEXT_RENDER_LAYER => self.ghost_text,
+			_ => 0.,
+		}
+	}
+}
+
+// One entry per FeverLevel; levels past the end use the last entry.
+#[derive(Resource, Debug)]
+pub struct DoubleVisionSettings {
+	pub ease_per_second: f32,		// How fast the strength follows a change of stage.
+	pub forced: Option<f32>,		// Overrides every stage (e.g. for debugging).
+	pub stages: Vec<DoubleVisionStage>,
+}
+impl Default for DoubleVisionSettings {
+	fn default() -> Self {
+		Self {
+			ease_per_second: 0.25,
+			forced: None,
+			stages: vec![
+				DoubleVisionStage::CALM,
+				DoubleVisionStage::CALM,
+				DoubleVisionStage { keyboard: 0., ghost_text: 0.35 },
+				DoubleVisionStage { keyboard: 0.3, ghost_text: 0.6 },
+				DoubleVisionStage { keyboard: 0.7, ghost_text: 1. },
+			],
+		}
+	}
+}
+impl DoubleVisionSettings {
+	pub fn stage(&self, fever_level: usize) -> DoubleVisionStage {
+		self.stages.get(fever_level)
+			.or(self.stages.last())
+			.copied()
+			.unwrap_or(DoubleVisionStage::CALM)
+	}
+}
+
+// Field order and types must match DoubleVision in the shader.
+#[derive(ShaderType, Clone, Copy, Debug, Default)]
+pub struct DoubleVisionUniform {
+	pub offset: Vec2,
+	pub strength: f32,
+	pub wobble_amplitude: f32,
+	pub wobble_frequency: f32,
+	pub wobble_wavelength: f32,
+	pub blur: f32,
+}
+impl From<&DoubleVision> for DoubleVisionUniform {
+	fn from(double_vision: &DoubleVision) -> Self {
+		Self {
+			offset: double_vision.offset,
+			strength: double_vision.strength.clamp(0., 1.),
+			wobble_amplitude: double_vision.wobble_amplitude,
+			wobble_frequency: double_vision.wobble_frequency,
+			wobble_wavelength: double_vision.wobble_wavelength,
+			blur: double_vision.blur,
+		}
+	}
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Clone, Debug)]
+pub struct DoubleVisionMaterial {
+	#[uniform(0)]
+	pub double_vision: DoubleVisionUniform,
+	#[texture(1)]
+	#[sampler(2)]
+	pub layer: Handle<Image>,
+}
+impl Material2d for DoubleVisionMaterial {
+	fn fragment_shader() -> ShaderRef {
+		DOUBLE_VISION_SHADER_PATH.into()
+	}
+
+	fn alpha_mode(&self) -> AlphaMode2d {
+		AlphaMode2d::Blend
+	}
+}
+
+// Each layer is drawn as one quad, at its own z among everything else; whatever is inside a layer
+// is ordered by its own z values, but only against the rest of that layer. So all of the keyboard
+// (loose keycaps and legends included) is under the ghost text, and typed glyphs (z 6.6) and
+// the mini player (z 7) are over both.
+const KEYBOARD_LAYER_Z: f32 = 1.;
+const GHOST_TEXT_LAYER_Z: f32 = 6.5;
+const DISTORTED_LAYERS: [(&str, usize, f32); 2] = [
+	("Keyboard", KEYBOARD_RENDER_LAYER, KEYBOARD_LAYER_Z),
+	("GhostText", GHOST_TEXT_RENDER_LAYER, GHOST_TEXT_LAYER_Z),
+];
+
+pub fn spawn_distorted_layers(
+	mut commands: Commands,
+	mut images: ResMut<Assets<Image>>,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<DoubleVisionMaterial>>,
+) {
+	let screen = VIRTUAL_RESOLUTION.as_vec2();
+	let quad = meshes.add(Rectangle::from_size(screen));
+
+	for (index, (name, layer, z)) in DISTORTED_LAYERS.into_iter().enumerate() {
+		let image = images.add(Image::new_target_texture(
+			VIRTUAL_RESOLUTION.x,
+			VIRTUAL_RESOLUTION.y,
+			TextureFormat::Rgba8UnormSrgb,
+			None,
+		));
+
+		// Rendered before the window's camera, which then draws the result.
+		commands.spawn((
+			Name::new(format!("{name}LayerCamera")),
+			Cleanup::<Quit>::new(),
+			LayerCamera,
+			Camera2d,
+			Camera {
+				order: -1 - index as isize,
+				clear_color: ClearColorConfig::Custom(Color::NONE),
+				..Default::default()
+			},
+			RenderTarget::Image(image.clone().into()),
+			RenderLayers::layer(layer),
+			Msaa::Off,
+		));
+
+		let double_vision = DoubleVision::default();
+		commands.spawn((
+			Name::new(format!("{name}Layer")),
+			Cleanup::<Quit>::new(),
+			DistortedLayer(layer),
+			double_vision,
+			DoubleVisionFromFever,
+			Mesh2d(quad.clone()),
+			MeshMaterial2d(materials.add(DoubleVisionMaterial {
+				double_vision: DoubleVisionUniform::from(&double_vision),
+				layer: image,
+			})),
+			Transform::from_xyz(0., 0., z),
+		));
+	}
+}
+
+pub fn ease_double_vision_to_fever(
+	time: Res<Time>,
+	fever_level: Res<FeverLevel>,
+	settings: Res<DoubleVisionSettings>,
+	mut layers: Query<(&DistortedLayer, &mut DoubleVision), With<DoubleVisionFromFever>>,
+) {
+	let stage = settings.stage(fever_level.0);
+	let max_step = settings.ease_per_second * time.delta_secs();
+	for (layer, mut double_vision) in &mut layers {
+		let target = settings.forced.unwrap_or(stage.strength(layer.0));
+		let step = (target - double_vision.strength).clamp(-max_step, max_step);
+		// Only touch the component when it moves, so the material isn't re-uploaded every frame.
+		if step != 0. {
+			double_vision.strength += step;
+		}
+	}
+}
+
+pub fn sync_double_vision_materials(
+	mut materials: ResMut<Assets<DoubleVisionMaterial>>,
+	layers: Query<(&DoubleVision, &MeshMaterial2d<DoubleVisionMaterial>), Changed<DoubleVision>>,
+) {
+	for (double_vision, material) in &layers {
+		if let Some(material) = materials.get_mut(&material.0) {
+			material.double_vision = DoubleVisionUniform::from(double_vision);
+		}
+	}
+}
@@ -0,0 +1,262 @@
+use bevy::prelude::{
+	Component, Resource, Event, On,
+	Entity, Name,
+	Query, Res, ResMut, With, Without,
+	Commands,
+	Transform,
+	Text2d, TextFont, TextColor,
+	Vec2,
+	default,
+};
+use bevy::app::Propagate;
+use bevy::camera::visibility::RenderLayers;
+use bevy::color::Alpha;
+
+use crate::{VIRTUAL_RESOLUTION, DEFAULT_KEYBOARD_HEIGHT};
+use crate::cleanup::{Cleanup, Quit};
+use crate::color_utils::ColorScheme;
+use crate::double_vision::GHOST_TEXT_RENDER_LAYER;
+use crate::draft_corruption::{DraftMorph, SubmitDraft};
+use crate::hurt::GlyphTyped;
+use crate::keyboard::{KeyTap, BACKSPACE_GLYPH, RETURN_GLYPH, can_type};
+use crate::sent_message::{NextIndex, spawn_sent_message};
+
+// =============================================================================
+// Draft: the message being typed, and the ghost prompt it is typed over
+// =============================================================================
+
+pub const DEFAULT_GHOST_PROMPT: &str = "Hi, I'm not feeling well today. I'm going to take a sick day and log off.";
+
+// The text the player is meant to type. Drawn faintly, one entity per glyph, under the typed text.
+#[derive(Resource, Debug)]
+pub struct GhostPrompt(pub String);
+impl Default for GhostPrompt {
+	fn default() -> Self {
+		Self(String::from(DEFAULT_GHOST_PROMPT))
+	}
+}
+
+// What the player has actually typed so far.
+#[derive(Resource, Debug, Default)]
+pub struct Draft(pub String);
+
+impl Draft {
+	// The character the ghost prompt expects next (compares logical text, never glyph positions).
+	pub fn next_expected(&self, ghost_prompt: &GhostPrompt) -> Option<char> {
+		ghost_prompt.0.chars().nth(self.0.chars().count())
+	}
+
+	// The keyboard has no punctuation keys, so whatever the prompt expects next that can't be typed
+	// (the comma in "Hi, ..." and the like) is filled in for the player, leaving a typeable glyph next.
+	pub fn fill_untypeable(&mut self, ghost_prompt: &GhostPrompt) {
+		while let Some(expected) = self.next_expected(ghost_prompt).filter(|expected| !can_type(*expected)) {
+			self.0.push(expected);
+		}
+	}
+}
+
+// Which character (by index into its string) a glyph entity draws.
+#[derive(Component, Debug)]
+pub struct GhostGlyph(pub usize);
+#[derive(Component, Debug)]
+pub struct TypedGlyph(pub usize);
+
+// Offset from a typed glyph's slot to where it is drawn (so it can drift out of line with its ghost).
+#[derive(Resource, Debug, Default)]
+pub struct TypedGlyphOffset(pub Vec2);
+
+// Draft area layout. Glyphs get a fixed advance and wrap by character count (no word wrap).
+const DRAFT_MARGIN_X: f32 = 60.;
+const DRAFT_TOP_ABOVE_KEYBOARD: f32 = 260.;
+const DRAFT_FONT_SIZE: f32 = 44.;
+const DRAFT_GLYPH_ADVANCE: f32 = 26.;
+const DRAFT_LINE_HEIGHT: f32 = 56.;
+pub const GHOST_GLYPH_Z: f32 = 6.5;			// Within the ghost text layer, which is drawn over the keyboard (see double_vision.rs).
+pub const TYPED_GLYPH_Z: f32 = 6.6;
+const GHOST_GLYPH_ALPHA: f32 = 0.5;
+
+// Where the glyph at `index` sits when nothing is messing with it.
+pub fn draft_glyph_slot(index: usize) -> Vec2 {
+	let width = VIRTUAL_RESOLUTION.x as f32;
+	let glyphs_per_line = ((width - 2. * DRAFT_MARGIN_X) / DRAFT_GLYPH_ADVANCE) as usize;
+	let column = index % glyphs_per_line;
+	let line = index / glyphs_per_line;
+
+	let left = -width * 0.5 + DRAFT_MARGIN_X + DRAFT_GLYPH_ADVANCE * 0.5;
+	let top = -(VIRTUAL_RESOLUTION.y as f32) * 0.5 + DEFAULT_KEYBOARD_HEIGHT + DRAFT_TOP_ABOVE_KEYBOARD;
+	Vec2::new(left + column as f32 * DRAFT_GLYPH_ADVANCE, top - line as f32 * DRAFT_LINE_HEIGHT)
+}
+
+fn glyph_text(glyph: char) -> (Text2d, TextFont) {
+	(
+		Text2d::new(glyph.to_string()),
+		TextFont { font_size: DRAFT_FONT_SIZE, ..default() },
+	)
+}
+
+// This runs when GhostPrompt changes (see App setup): respawn one entity per prompt glyph.
+pub fn rebuild_ghost_glyphs(
+	mut commands: Commands,
+	ghost_prompt: Res<GhostPrompt>,
+	color_scheme: Res<ColorScheme>,
+	ghost_glyphs: Query<Entity, With<GhostGlyph>>,
+) {
+	for entity in &ghost_glyphs {
+		commands.entity(entity).despawn();
+	}
+
+	let color = color_scheme.sys_text_color.with_alpha(GHOST_GLYPH_ALPHA);
+	for (index, glyph) in ghost_prompt.0.chars().enumerate() {
+		commands.spawn((
+			Name::new(format!("GhostGlyph {index}")),
+			Cleanup::<Quit>::new(),
+			GhostGlyph(index),
+			Propagate(RenderLayers::layer(GHOST_TEXT_RENDER_LAYER)),
+			glyph_text(glyph),
+			TextColor(color),
+			Transform::from_translation(draft_glyph_slot(index).extend(GHOST_GLYPH_Z)),
+		));
+	}
+}
+
+// This runs when Draft changes (see App setup): respawn one entity per typed glyph.
+pub fn rebuild_typed_glyphs(
+	mut commands: Commands,
+	draft: Res<Draft>,
+	typed_glyph_offset: Res<TypedGlyphOffset>,
+	color_scheme: Res<ColorScheme>,
+	typed_glyphs: Query<Entity, With<TypedGlyph>>,
+) {
+	for entity in &typed_glyphs {
+		commands.entity(entity).despawn();
+	}
+
+	for (index, glyph) in draft.0.chars().enumerate() {
+		let position = draft_glyph_slot(index) + typed_glyph_offset.0;
+		commands.spawn((
+			Name::new(format!("TypedGlyph {index}")),
+			Cleanup::<Quit>::new(),
+			TypedGlyph(index),
+			glyph_text(glyph),
+			TextColor(color_scheme.key_text_color),
+			Transform::from_translation(position.extend(TYPED_GLYPH_Z)),
+		));
+	}
+}
+
+// This runs when TypedGlyphOffset changes (see App setup).
+pub fn offset_typed_glyphs(
+	typed_glyph_offset: Res<TypedGlyphOffset>,
+	mut typed_glyphs: Query<(&TypedGlyph, &mut Transform)>,
+) {
+	for (typed_glyph, mut transform) in &mut typed_glyphs {
+		let position = draft_glyph_slot(typed_glyph.0) + typed_glyph_offset.0;
+		transform.translation.x = position.x;
+		transform.translation.y = position.y;
+	}
+}
+
+// This runs when ColorScheme changes (see App setup).
+pub fn update_draft_colors_on_color_scheme_change(
+	color_scheme: Res<ColorScheme>,
+	mut ghost_glyphs: Query<&mut TextColor, (With<GhostGlyph>, Without<TypedGlyph>)>,
+	mut typed_glyphs: Query<&mut TextColor, With<TypedGlyph>>,
+) {
+	for mut color in &mut ghost_glyphs {
+		color.0 = color_scheme.sys_text_color.with_alpha(GHOST_GLYPH_ALPHA);
+	}
+	for mut color in &mut typed_glyphs {
+		color.0 = color_scheme.key_text_color;
+	}
+}
+
+// Trigger to send whatever is in the draft as one of the player's messages, as is.
+// (Return triggers SubmitDraft instead, which may corrupt the draft first; see draft_corruption.rs.)
+#[derive(Event, Debug)]
+pub struct SendDraft;
+
+pub fn on_key_tap_edit_draft(
+	event: On<KeyTap>,
+	mut commands: Commands,
+	mut draft: ResMut<Draft>,
+	ghost_prompt: Res<GhostPrompt>,
+	draft_morph: Res<DraftMorph>,
+) {
+	if draft_morph.0.is_some() {
+		return;
+	}
+	match event.glyph {
+		BACKSPACE_GLYPH => {
+			draft.0.pop();
+		}
+		RETURN_GLYPH => {
+			// Finish off the trailing punctuation ("...log off.") too.
+			draft.fill_untypeable(&ghost_prompt);
+			commands.trigger(SubmitDraft);
+		}
+		glyph => {
+			draft.fill_untypeable(&ghost_prompt);
+			commands.trigger(GlyphTyped { glyph, expected: draft.next_expected(&ghost_prompt) });
+			draft.0.push(glyph);
+		}
+	}
+}
+
+pub fn on_send_draft(
+	_event: On<SendDraft>,
+	mut commands: Commands,
+	mut draft: ResMut<Draft>,
+	mut next_index: ResMut<NextIndex>,
+	color_scheme: Res<ColorScheme>,
+) {
+	if draft.0.trim().is_empty() {
+		return;
+	}
+	spawn_sent_message(&mut commands, &mut next_index, &color_scheme, &draft.0, true, false, None);
+	draft.0.clear();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn prompt(text: &str) -> GhostPrompt {
+		GhostPrompt(String::from(text))
+	}
+
+	#[test]
+	fn next_expected_follows_the_logical_text() {
+		let ghost_prompt = prompt("Hi, I'm sick 😷.");
+		assert_eq!(Draft(String::new()).next_expected(&ghost_prompt), Some('H'));
+		assert_eq!(Draft(String::from("Hi")).next_expected(&ghost_prompt), Some(','));
+		// Counted in characters, not bytes, so the emoji is one step.
+		assert_eq!(Draft(String::from("Hi, I'm sick 😷")).next_expected(&ghost_prompt), Some('.'));
+		assert_eq!(Draft(String::from("Hi, I'm sick 😷.")).next_expected(&ghost_prompt), None);
+		assert_eq!(Draft(String::from("Hi, I'm sick 😷. ")).next_expected(&ghost_prompt), None);
+	}
+
+	#[test]
+	fn fill_untypeable_stops_at_the_next_typeable_glyph() {
+		let ghost_prompt = prompt("Hi, I'm out...");
+
+		let mut draft = Draft(String::from("Hi"));
+		draft.fill_untypeable(&ghost_prompt);
+		assert_eq!(draft.0, "Hi,");
+
+		// Space and letters (in either case) are on the keyboard, so nothing is filled in.
+		draft.fill_untypeable(&ghost_prompt);
+		assert_eq!(draft.0, "Hi,");
+		let mut draft = Draft(String::from("Hi, "));
+		draft.fill_untypeable(&ghost_prompt);
+		assert_eq!(draft.0, "Hi, ");
+
+		let mut draft = Draft(String::from("Hi, I"));
+		draft.fill_untypeable(&ghost_prompt);
+		assert_eq!(draft.0, "Hi, I'");
+
+		// Trailing punctuation is filled in all the way to the end.
+		let mut draft = Draft(String::from("Hi, I'm out"));
+		draft.fill_untypeable(&ghost_prompt);
+		assert_eq!(draft.0, "Hi, I'm out...");
+	}
+}
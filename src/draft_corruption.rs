@@ -0,0 +1,318 @@
+use bevy::prelude::{
+	Resource, Event, On, In,
+	Asset, Assets, AssetServer, Handle,
+	Res, ResMut,
+	Commands,
+	Time,
+	TypePath,
+};
+use bevy::asset::{AssetLoader, LoadContext, io::Reader};
+use rand::{Rng, seq::{IndexedRandom, SliceRandom}};
+
+use crate::FeverLevel;
+use crate::blink::BlinkKind;
+use crate::draft::{Draft, SendDraft};
+use crate::power_cycle::{PowerOut, PowerCyclePolicy};
+
+// =============================================================================
+// Draft corruption: the draft gets rewritten into something else just before it goes out
+// =============================================================================
+
+// Corruption works on the Draft buffer itself, so whatever the glyphs morph into is exactly
+// what on_send_draft hands to spawn_sent_message.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CorruptionGenerator {
+	KeyboardMash,		// asdfjkl;asdf - as if the player faceplanted onto the keys.
+	LetterShuffle,		// Letters shuffled within each word; spaces and punctuation stay put.
+	CannedLine,			// A whole embarrassing line from assets/text/embarrassing.lines.
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DraftCorruptionStage {
+	pub send_chance: f32,							// Chance (0 to 1) a sent draft gets rewritten first.
+	pub blink_chance: f32,							// Chance an involuntary blink rewrites the draft.
+	pub generators: &'static [CorruptionGenerator],	// Picked from at random; empty disables corruption.
+}
+impl DraftCorruptionStage {
+	const CALM: Self = Self {
+		send_chance: 0.,
+		blink_chance: 0.,
+		generators: &[],
+	};
+}
+
+// One entry per FeverLevel; levels past the end use the last entry.
+#[derive(Resource, Debug)]
+pub struct DraftCorruptionSettings {
+	pub morph_seconds: f32,		// How long the old text takes to morph into the new.
+	pub stages: Vec<DraftCorruptionStage>,
+}
+impl Default for DraftCorruptionSettings {
+	fn default() -> Self {
+		use CorruptionGenerator::*;
+		Self {
+			morph_seconds: 1.2,
+			stages: vec![
+				DraftCorruptionStage::CALM,
+				DraftCorruptionStage::CALM,
+				DraftCorruptionStage { send_chance: 0.25, blink_chance: 0., generators: &[LetterShuffle] },
+				DraftCorruptionStage { send_chance: 0.5, blink_chance: 0.3, generators: &[LetterShuffle, KeyboardMash] },
+				DraftCorruptionStage { send_chance: 0.8, blink_chance: 0.6, generators: &[LetterShuffle, KeyboardMash, CannedLine] },
+			],
+		}
+	}
+}
+impl DraftCorruptionSettings {
+	pub fn stage(&self, fever_level: usize) -> DraftCorruptionStage {
+		self.stages.get(fever_level)
+			.or(self.stages.last())
+			.copied()
+			.unwrap_or(DraftCorruptionStage::CALM)
+	}
+}
+
+// -----------------------------------------------------------------------------
+// Canned lines asset: plain text, one line per entry
+// -----------------------------------------------------------------------------
+
+pub const CANNED_LINES_PATH: &str = "text/embarrassing.lines";
+
+#[derive(Asset, TypePath, Debug)]
+pub struct CannedLines(pub Vec<String>);
+
+#[derive(TypePath, Default)]
+pub struct CannedLinesLoader;
+
+impl AssetLoader for CannedLinesLoader {
+	type Asset = CannedLines;
+	type Settings = ();
+	type Error = std::io::Error;
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &(),
+		_load_context: &mut LoadContext<'_>,
+	) -> Result<CannedLines, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let lines = String::from_utf8_lossy(&bytes)
+			.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty() && !line.starts_with('#'))
+			.map(String::from)
+			.collect();
+		Ok(CannedLines(lines))
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["lines"]
+	}
+}
+
+#[derive(Resource, Debug)]
+pub struct CannedLinesHandle(pub Handle<CannedLines>);
+
+pub fn load_canned_lines(mut commands: Commands, asset_server: Res<AssetServer>) {
+	commands.insert_resource(CannedLinesHandle(asset_server.load(CANNED_LINES_PATH)));
+}
+
+// -----------------------------------------------------------------------------
+// Generators
+// -----------------------------------------------------------------------------
+
+const MASH_ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl;", "zxcvbnm,."];
+
+fn keyboard_mash(length: usize) -> String {
+	let mut rng = rand::rng();
+	let mut mash = String::new();
+	while mash.chars().count() < length.max(6) {
+		// Runs of neighbouring keys, the way a palm or forehead would hit them.
+		let row: Vec<char> = MASH_ROWS.choose(&mut rng).copied().unwrap_or_default().chars().collect();
+		let start = rng.random_range(0..row.len());
+		let run = rng.random_range(2..=5);
+		for offset in 0..run {
+			mash.push(row[(start + offset) % row.len()]);
+		}
+		if rng.random_bool(0.2) {
+			mash.push(' ');
+		}
+	}
+	mash
+}
+
+fn letter_shuffle(text: &str) -> String {
+	let mut rng = rand::rng();
+	let mut shuffled = String::new();
+	let mut word: Vec<char> = Vec::new();
+	for glyph in text.chars().chain(std::iter::once(' ')) {
+		if glyph.is_alphanumeric() {
+			word.push(glyph);
+			continue;
+		}
+		word.shuffle(&mut rng);
+		shuffled.extend(word.drain(..));
+		shuffled.push(glyph);
+	}
+	shuffled.pop();
+	shuffled
+}
+
+pub fn corrupted_text(generator: CorruptionGenerator, text: &str, canned_lines: Option<&CannedLines>) -> String {
+	match generator {
+		CorruptionGenerator::KeyboardMash => keyboard_mash(text.chars().count()),
+		CorruptionGenerator::LetterShuffle => letter_shuffle(text),
+		CorruptionGenerator::CannedLine => canned_lines
+			.and_then(|canned_lines| canned_lines.0.choose(&mut rand::rng()).cloned())
+			// Not loaded yet (or empty): mashing is always available.
+			.unwrap_or_else(|| keyboard_mash(text.chars().count())),
+	}
+}
+
+// -----------------------------------------------------------------------------
+// Morphing the draft from what was typed into what gets sent
+// -----------------------------------------------------------------------------
+
+const MORPH_SCRAMBLE_GLYPHS: &str = "abcdefghijklmnopqrstuvwxyz#%&@?!*";
+// Glyphs still morphing change this many times a second.
+const MORPH_FLICKER_RATE: f32 = 20.;
+
+// The draft is mid-morph while this is Some; typing is ignored until it finishes.
+#[derive(Resource, Debug, Default)]
+pub struct DraftMorph(pub Option<DraftMorphProgress>);
+
+#[derive(Debug)]
+pub struct DraftMorphProgress {
+	pub target: Vec<char>,
+	pub length: usize,				// Longest of the old and new text; every slot gets a turn to morph.
+	pub elapsed: f32,
+	pub send_when_done: bool,
+}
+
+// Trigger to rewrite the draft. Without a generator one is picked from the current fever stage.
+#[derive(Event, Debug)]
+pub struct CorruptDraft {
+	pub generator: Option<CorruptionGenerator>,
+	pub send_when_done: bool,
+}
+
+// Return asks for the draft to be sent; this is where the fever gets a chance to rewrite it first.
+#[derive(Event, Debug)]
+pub struct SubmitDraft;
+
+pub fn on_submit_draft(
+	_event: On<SubmitDraft>,
+	mut commands: Commands,
+	fever_level: Res<FeverLevel>,
+	settings: Res<DraftCorruptionSettings>,
+	draft: Res<Draft>,
+	draft_morph: Res<DraftMorph>,
+) {
+	if draft_morph.0.is_some() {
+		return;
+	}
+	let stage = settings.stage(fever_level.0);
+	if !draft.0.trim().is_empty() && !stage.generators.is_empty() && rand::rng().random_bool(stage.send_chance.clamp(0., 1.) as f64) {
+		commands.trigger(CorruptDraft { generator: None, send_when_done: true });
+	} else {
+		commands.trigger(SendDraft);
+	}
+}
+
+pub fn on_corrupt_draft(
+	event: On<CorruptDraft>,
+	fever_level: Res<FeverLevel>,
+	settings: Res<DraftCorruptionSettings>,
+	canned_lines_handle: Option<Res<CannedLinesHandle>>,
+	canned_lines: Res<Assets<CannedLines>>,
+	draft: Res<Draft>,
+	mut draft_morph: ResMut<DraftMorph>,
+) {
+	if draft_morph.0.is_some() || draft.0.trim().is_empty() {
+		return;
+	}
+	let Some(generator) = event.generator
+		.or_else(|| settings.stage(fever_level.0).generators.choose(&mut rand::rng()).copied())
+	else {
+		return;
+	};
+
+	let canned_lines = canned_lines_handle.and_then(|handle| canned_lines.get(&handle.0));
+	let target: Vec<char> = corrupted_text(generator, &draft.0, canned_lines).chars().collect();
+	draft_morph.0 = Some(DraftMorphProgress {
+		length: target.len().max(draft.0.chars().count()),
+		target,
+		elapsed: 0.,
+		send_when_done: event.send_when_done,
+	});
+}
+
+// Glyphs settle on their new character left to right; until then they flicker through junk.
+pub fn advance_draft_morph(
+	mut commands: Commands,
+	time: Res<Time>,
+	settings: Res<DraftCorruptionSettings>,
+	mut draft_morph: ResMut<DraftMorph>,
+	mut draft: ResMut<Draft>,
+) {
+	let Some(progress) = draft_morph.0.as_mut() else {
+		return;
+	};
+	let previous_flicker = (progress.elapsed * MORPH_FLICKER_RATE) as u32;
+	progress.elapsed += time.delta_secs();
+	let t = (progress.elapsed / settings.morph_seconds.max(f32::EPSILON)).min(1.);
+
+	if t >= 1. {
+		draft.0 = progress.target.iter().collect();
+		let send = progress.send_when_done;
+		draft_morph.0 = None;
+		if send {
+			commands.trigger(SendDraft);
+		}
+		return;
+	}
+	if (progress.elapsed * MORPH_FLICKER_RATE) as u32 == previous_flicker {
+		return;
+	}
+
+	let mut rng = rand::rng();
+	let scramble: Vec<char> = MORPH_SCRAMBLE_GLYPHS.chars().collect();
+	let mut morphed = String::new();
+	for index in 0..progress.length {
+		// The last slot settles a little before the end so the finished line can sit for a beat.
+		let settle_at = (index + 1) as f32 / progress.length as f32 * 0.8;
+		if t >= settle_at {
+			if let Some(glyph) = progress.target.get(index) {
+				morphed.push(*glyph);
+			}
+		} else {
+			morphed.push(scramble.choose(&mut rng).copied().unwrap_or('#'));
+		}
+	}
+	draft.0 = morphed;
+}
+
+// Power-out clears the draft (unless the policy keeps it), so a morph underway goes with it - rather than
+// rewriting the empty draft and sending it once the power is back.
+pub fn on_power_out_stop_draft_morph(
+	_event: On<PowerOut>,
+	policy: Res<PowerCyclePolicy>,
+	mut draft_morph: ResMut<DraftMorph>,
+) {
+	if !policy.keep_draft && draft_morph.0.is_some() {
+		draft_morph.0 = None;
+	}
+}
+
+// Blink reaction: at high fever, opening your eyes can reveal a draft you never wrote.
+pub fn corrupt_draft_on_blink(
+	In(kind): In<BlinkKind>,
+	mut commands: Commands,
+	fever_level: Res<FeverLevel>,
+	settings: Res<DraftCorruptionSettings>,
+) {
+	let stage = settings.stage(fever_level.0);
+	if kind == BlinkKind::Involuntary && rand::rng().random_bool(stage.blink_chance.clamp(0., 1.) as f64) {
+		commands.trigger(CorruptDraft { generator: None, send_when_done: false });
+	}
+}
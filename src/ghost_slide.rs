@@ -0,0 +1,190 @@
+use bevy::prelude::{
+	Component, Resource, On, In,
+	Entity,
+	Query, Res, ResMut, With, Without,
+	Commands,
+	Time, Transform,
+	Vec2,
+};
+
+use crate::{VIRTUAL_RESOLUTION, FeverLevel};
+use crate::blink::BlinkKind;
+use crate::draft::{GhostGlyph, TypedGlyphOffset, draft_glyph_slot};
+use crate::noise_utils::*;
+use crate::power_cycle::{PowerOut, PowerCyclePolicy};
+
+// =============================================================================
+// Ghost prompt sliding down the screen
+// =============================================================================
+
+// Each ghost glyph slides down on its own (at its own pace, with a little sideways wobble)
+// until it lands on the bottom of the screen - i.e. on the desk, in front of the keyboard.
+// What puts the glyphs back depends on the current fever stage's GhostResetPolicy.
+// Typing is always checked against GhostPrompt itself, so none of this changes what counts as correct.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GhostResetPolicy {
+	AfterSeconds(f32),		// Snap back once the slide has been going this long.
+	OnPowerCycle,			// Stay put until the computer power cycles.
+	Never,					// Stay put, even through power cycles.
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GhostSlideStage {
+	pub slide_speed: f32,		// Average fall speed in world units per second (0 disables sliding).
+	pub reset: GhostResetPolicy,
+	pub misalignment: Vec2,		// How far typed glyphs are drawn from their ghost counterparts.
+}
+impl GhostSlideStage {
+	const CALM: Self = Self {
+		slide_speed: 0.,
+		reset: GhostResetPolicy::AfterSeconds(0.),
+		misalignment: Vec2::ZERO,
+	};
+}
+
+// One entry per FeverLevel; levels past the end use the last entry.
+#[derive(Resource, Debug)]
+pub struct GhostSlideSettings {
+	pub stages: Vec<GhostSlideStage>,
+}
+impl Default for GhostSlideSettings {
+	fn default() -> Self {
+		Self {
+			stages: vec![
+				GhostSlideStage::CALM,
+				GhostSlideStage::CALM,
+				GhostSlideStage { slide_speed: 45., reset: GhostResetPolicy::AfterSeconds(8.), misalignment: Vec2::ZERO },
+				GhostSlideStage { slide_speed: 70., reset: GhostResetPolicy::OnPowerCycle, misalignment: Vec2::ZERO },
+				GhostSlideStage { slide_speed: 90., reset: GhostResetPolicy::Never, misalignment: Vec2::new(14., -9.) },
+			],
+		}
+	}
+}
+impl GhostSlideSettings {
+	pub fn stage(&self, fever_level: usize) -> GhostSlideStage {
+		self.stages.get(fever_level)
+			.or(self.stages.last())
+			.copied()
+			.unwrap_or(GhostSlideStage::CALM)
+	}
+}
+
+// How long the ghost prompt has been sliding since it was last put back.
+#[derive(Resource, Debug, Default)]
+pub struct GhostSlideElapsed(pub f32);
+
+// How far a ghost glyph has slid from its slot.
+#[derive(Component, Debug, Default)]
+pub struct GhostSlide {
+	pub offset: Vec2,
+}
+
+// Height above the bottom edge of the screen where sliding glyphs come to rest.
+const GHOST_FLOOR_MARGIN: f32 = 30.;
+const GHOST_WOBBLE: f32 = 12.;
+
+// Ghost glyphs are respawned whenever the prompt changes; give the new ones somewhere to keep their offset.
+pub fn init_ghost_slides(
+	mut commands: Commands,
+	ghost_glyphs: Query<Entity, (With<GhostGlyph>, Without<GhostSlide>)>,
+) {
+	for entity in &ghost_glyphs {
+		commands.entity(entity).insert(GhostSlide::default());
+	}
+}
+
+pub fn slide_ghost_glyphs(
+	time: Res<Time>,
+	fever_level: Res<FeverLevel>,
+	settings: Res<GhostSlideSettings>,
+	mut elapsed: ResMut<GhostSlideElapsed>,
+	mut ghost_glyphs: Query<(&GhostGlyph, &mut GhostSlide, &mut Transform)>,
+) {
+	let stage = settings.stage(fever_level.0);
+	if stage.slide_speed <= 0. {
+		// Not sliding at this stage: put back anything the last stage left lying around.
+		if elapsed.0 > 0. {
+			reset_ghost_slides(&mut elapsed, &mut ghost_glyphs);
+		}
+		return;
+	}
+
+	elapsed.0 += time.delta_secs();
+	if let GhostResetPolicy::AfterSeconds(seconds) = stage.reset && elapsed.0 >= seconds {
+		reset_ghost_slides(&mut elapsed, &mut ghost_glyphs);
+		return;
+	}
+
+	let dt = time.delta_secs();
+	let floor = -(VIRTUAL_RESOLUTION.y as f32) * 0.5 + GHOST_FLOOR_MARGIN;
+	for (ghost_glyph, mut slide, mut transform) in &mut ghost_glyphs {
+		let seed = ghost_glyph.0 as f32 * 0.37;
+		let slot = draft_glyph_slot(ghost_glyph.0);
+
+		// Each glyph falls at somewhere between half and one and a half times the stage speed.
+		let speed = stage.slide_speed * (1. + smooth_noise(seed, seed) * 0.5);
+		slide.offset.y = (slide.offset.y - speed * dt).max(floor - slot.y);
+		slide.offset.x = smooth_noise(elapsed.0, seed) * GHOST_WOBBLE;
+
+		let position = slot + slide.offset;
+		transform.translation.x = position.x;
+		transform.translation.y = position.y;
+	}
+}
+
+// Put every ghost glyph back in its slot.
+pub fn reset_ghost_slides(
+	elapsed: &mut GhostSlideElapsed,
+	ghost_glyphs: &mut Query<(&GhostGlyph, &mut GhostSlide, &mut Transform)>,
+) {
+	elapsed.0 = 0.;
+	for (ghost_glyph, mut slide, mut transform) in ghost_glyphs.iter_mut() {
+		slide.offset = Vec2::ZERO;
+		let slot = draft_glyph_slot(ghost_glyph.0);
+		transform.translation.x = slot.x;
+		transform.translation.y = slot.y;
+	}
+}
+
+// Early on a power cycle puts the ghost prompt back; later stages (or the policy) leave it where it fell.
+pub fn on_power_out_reset_ghost(
+	_event: On<PowerOut>,
+	fever_level: Res<FeverLevel>,
+	settings: Res<GhostSlideSettings>,
+	policy: Res<PowerCyclePolicy>,
+	mut elapsed: ResMut<GhostSlideElapsed>,
+	mut ghost_glyphs: Query<(&GhostGlyph, &mut GhostSlide, &mut Transform)>,
+) {
+	if policy.keep_ghost_position || settings.stage(fever_level.0).reset == GhostResetPolicy::Never {
+		return;
+	}
+	reset_ghost_slides(&mut elapsed, &mut ghost_glyphs);
+}
+
+// Blink reaction: a deliberate blink puts the ghost prompt back, unless the stage never lets it reset.
+pub fn reset_ghost_on_blink(
+	In(kind): In<BlinkKind>,
+	fever_level: Res<FeverLevel>,
+	settings: Res<GhostSlideSettings>,
+	mut elapsed: ResMut<GhostSlideElapsed>,
+	mut ghost_glyphs: Query<(&GhostGlyph, &mut GhostSlide, &mut Transform)>,
+) {
+	if kind != BlinkKind::Voluntary || settings.stage(fever_level.0).reset == GhostResetPolicy::Never {
+		return;
+	}
+	reset_ghost_slides(&mut elapsed, &mut ghost_glyphs);
+}
+
+// Keep typed glyphs offset from their ghosts as the current stage asks.
+// This runs when FeverLevel changes (see App setup).
+pub fn apply_ghost_misalignment(
+	fever_level: Res<FeverLevel>,
+	settings: Res<GhostSlideSettings>,
+	mut typed_glyph_offset: ResMut<TypedGlyphOffset>,
+) {
+	let misalignment = settings.stage(fever_level.0).misalignment;
+	if typed_glyph_offset.0 != misalignment {
+		typed_glyph_offset.0 = misalignment;
+	}
+}
@@ -0,0 +1,253 @@
+use bevy::prelude::{
+	Component, Resource, Event, On,
+	Name,
+	Query, Res, ResMut, With, Without, Or,
+	Commands,
+	Time, Transform, Visibility,
+	Color, Vec2,
+};
+use bevy::color::Alpha;
+use bevy_vector_shapes::prelude::*;
+use rand::Rng;
+
+use crate::{VIRTUAL_RESOLUTION, FeverLevel};
+use crate::audio::AudioManager;
+use crate::cleanup::{Cleanup, Quit};
+use crate::keyboard::{KeyboardBase, KeySocket, Keycap, Detached, Held, can_type};
+use crate::noise_utils::*;
+
+// =============================================================================
+// Hurt: what a wrong key (or a bad fever) does to you
+// =============================================================================
+
+// Typed glyphs are checked against the ghost prompt as they go into the draft (see GlyphTyped).
+// A mistake hurts: the hurt sound plays, the screen edge flushes red and the keyboard shakes.
+// Later fever stages hurt at random, or on every key, right or wrong.
+
+pub const HURT_SOUND_PATH: &str = "audio/sfx/hitHurt.wav";
+
+// Triggered (by the draft) for every glyph typed into it, along with what the ghost prompt wanted there
+// (always something the keyboard can type, unless the prompt has run out).
+#[derive(Event, Debug)]
+pub struct GlyphTyped {
+	pub glyph: char,
+	pub expected: Option<char>,
+}
+impl GlyphTyped {
+	// The keyboard has no caps lock, so case doesn't count against the player; nor does a glyph it has
+	// no key for (the draft fills those in before checking, see Draft::fill_untypeable, so this is a backstop).
+	// Anything typed once the prompt has run out (expected is None) is a mistake, on purpose: the prompt
+	// is all the player is meant to type, so even a stray space before Return hurts.
+	pub fn is_correct(&self) -> bool {
+		self.expected.is_some_and(|expected| !can_type(expected) || expected.to_lowercase().eq(self.glyph.to_lowercase()))
+	}
+}
+
+// Trigger to hurt the player.
+#[derive(Event, Debug)]
+pub struct Hurt;
+
+#[derive(Clone, Copy, Debug)]
+pub struct HurtStage {
+	pub random_chance: f32,		// Chance (0 to 1) that a correct key hurts anyway.
+	pub every_key: bool,		// Every key hurts, right or wrong.
+}
+impl HurtStage {
+	const CALM: Self = Self {
+		random_chance: 0.,
+		every_key: false,
+	};
+}
+
+// One entry per FeverLevel; levels past the end use the last entry.
+#[derive(Resource, Debug)]
+pub struct HurtSettings {
+	pub tint_seconds: f32,
+	pub shake_seconds: f32,
+	pub shake_magnitude: f32,		// World units.
+	pub stages: Vec<HurtStage>,
+}
+impl Default for HurtSettings {
+	fn default() -> Self {
+		Self {
+			tint_seconds: 0.45,
+			shake_seconds: 0.35,
+			shake_magnitude: 18.,
+			stages: vec![
+				HurtStage::CALM,
+				HurtStage::CALM,
+				HurtStage::CALM,
+				HurtStage { random_chance: 0.1, every_key: false },
+				HurtStage { random_chance: 0., every_key: true },
+			],
+		}
+	}
+}
+impl HurtSettings {
+	pub fn stage(&self, fever_level: usize) -> HurtStage {
+		self.stages.get(fever_level)
+			.or(self.stages.last())
+			.copied()
+			.unwrap_or(HurtStage::CALM)
+	}
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct HurtState {
+	pub tint_remaining: f32,
+	pub shake_remaining: f32,
+	pub shake_applied: Vec2,		// Offset currently added to the keyboard's transforms.
+}
+
+pub fn on_glyph_typed_check(
+	event: On<GlyphTyped>,
+	mut commands: Commands,
+	fever_level: Res<FeverLevel>,
+	settings: Res<HurtSettings>,
+) {
+	let stage = settings.stage(fever_level.0);
+	let hurts = !event.is_correct()
+		|| stage.every_key
+		|| rand::rng().random_bool(stage.random_chance.clamp(0., 1.) as f64);
+	if hurts {
+		commands.trigger(Hurt);
+	}
+}
+
+pub fn on_hurt(
+	_event: On<Hurt>,
+	mut audio: ResMut<AudioManager>,
+	settings: Res<HurtSettings>,
+	mut hurt_state: ResMut<HurtState>,
+) {
+	audio.play_sfx(HURT_SOUND_PATH);
+	hurt_state.tint_remaining = settings.tint_seconds;
+	hurt_state.shake_remaining = settings.shake_seconds;
+}
+
+// -----------------------------------------------------------------------------
+// Red screen edge
+// -----------------------------------------------------------------------------
+
+const HURT_TINT_Z: f32 = 37.;		// Over the lighting, so it still shows in the dark; under the eyelids.
+const HURT_TINT_COLOR: Color = Color::srgb(0.9, 0.05, 0.08);
+const HURT_TINT_BANDS: usize = 5;
+const HURT_TINT_BAND_WIDTH: f32 = 18.;
+const HURT_TINT_ALPHA: f32 = 0.55;
+
+// One band of the red edge; the outermost bands are the most opaque.
+#[derive(Component, Debug)]
+pub struct HurtTintBand(pub f32);
+
+pub fn spawn_hurt_tint(mut commands: Commands) {
+	let screen = VIRTUAL_RESOLUTION.as_vec2();
+	commands.spawn((
+		Name::new("HurtTint"),
+		Cleanup::<Quit>::new(),
+		Transform::from_xyz(0., 0., HURT_TINT_Z),
+		Visibility::default(),
+	)).with_children(|parent| {
+		for band in 0..HURT_TINT_BANDS {
+			// Hollow rects, each one band further in from the edge; thickness reaches inwards.
+			let inset = band as f32 * HURT_TINT_BAND_WIDTH;
+			let strength = 1. - band as f32 / HURT_TINT_BANDS as f32;
+			parent.spawn((
+				Name::new("HurtTintBand"),
+				HurtTintBand(strength * strength),
+				ShapeBundle::rect(
+					&ShapeConfig {
+						color: HURT_TINT_COLOR.with_alpha(0.),
+						hollow: true,
+						thickness: HURT_TINT_BAND_WIDTH,
+						..ShapeConfig::default_2d()
+					},
+					screen - Vec2::splat(inset * 2.),
+				),
+			));
+		}
+	});
+}
+
+pub fn fade_hurt_tint(
+	time: Res<Time>,
+	settings: Res<HurtSettings>,
+	mut hurt_state: ResMut<HurtState>,
+	mut bands: Query<(&HurtTintBand, &mut ShapeFill)>,
+) {
+	if hurt_state.tint_remaining <= 0. {
+		return;
+	}
+	hurt_state.tint_remaining = (hurt_state.tint_remaining - time.delta_secs()).max(0.);
+	let fade = hurt_state.tint_remaining / settings.tint_seconds.max(f32::EPSILON);
+	for (band, mut fill) in &mut bands {
+		fill.color = HURT_TINT_COLOR.with_alpha(HURT_TINT_ALPHA * band.0 * fade);
+	}
+}
+
+// -----------------------------------------------------------------------------
+// Keyboard shake
+// -----------------------------------------------------------------------------
+
+// The shake is only ever added on for drawing: it goes on in PostUpdate (before transform propagation)
+// and comes off again in First, so everything in between sees the keyboard where it really is.
+// Legends follow their keycaps, so they shake along without being listed here.
+type ShakenKeyboardFilter = (Or<(With<KeyboardBase>, With<KeySocket>, With<Keycap>)>, Without<Detached>, Without<Held>);
+
+pub fn unshake_keyboard(
+	mut hurt_state: ResMut<HurtState>,
+	mut transforms: Query<&mut Transform, ShakenKeyboardFilter>,
+) {
+	if hurt_state.shake_applied == Vec2::ZERO {
+		return;
+	}
+	for mut transform in &mut transforms {
+		transform.translation -= hurt_state.shake_applied.extend(0.);
+	}
+	hurt_state.shake_applied = Vec2::ZERO;
+}
+
+pub fn shake_keyboard(
+	time: Res<Time>,
+	settings: Res<HurtSettings>,
+	mut hurt_state: ResMut<HurtState>,
+	mut transforms: Query<&mut Transform, ShakenKeyboardFilter>,
+) {
+	if hurt_state.shake_remaining <= 0. {
+		return;
+	}
+	hurt_state.shake_remaining = (hurt_state.shake_remaining - time.delta_secs()).max(0.);
+	let fade = hurt_state.shake_remaining / settings.shake_seconds.max(f32::EPSILON);
+	let offset = smooth_noise_2d(time.elapsed_secs() * 40., 5.7) * settings.shake_magnitude * fade;
+
+	for mut transform in &mut transforms {
+		transform.translation += offset.extend(0.);
+	}
+	hurt_state.shake_applied = offset;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn typed(glyph: char, expected: Option<char>) -> GlyphTyped {
+		GlyphTyped { glyph, expected }
+	}
+
+	#[test]
+	fn is_correct_ignores_case_and_untypeable_glyphs() {
+		assert!(typed('h', Some('h')).is_correct());
+		assert!(typed('h', Some('H')).is_correct());
+		assert!(typed(' ', Some(' ')).is_correct());
+		assert!(!typed('j', Some('h')).is_correct());
+		assert!(!typed(' ', Some('h')).is_correct());
+		// No key for these, so whatever was typed doesn't count against the player.
+		assert!(typed('s', Some(',')).is_correct());
+		assert!(typed('s', Some('😷')).is_correct());
+	}
+
+	#[test]
+	fn typing_past_the_prompt_is_a_mistake() {
+		assert!(!typed('s', None).is_correct());
+		assert!(!typed(' ', None).is_correct());
+	}
+}
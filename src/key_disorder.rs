@@ -0,0 +1,112 @@
+use bevy::prelude::{
+	Resource, Res,
+	Query, Without,
+	Children,
+	Time, Transform,
+	Color, Vec3,
+};
+use bevy::color::Mix;
+use bevy_vector_shapes::prelude::*;
+
+use crate::color_utils::ColorScheme;
+use crate::keyboard::{Keycap, KeySocket, KeyColorRole, Detached, KEYCAP_Z, DETACHED_KEYCAP_Z};
+use crate::noise_utils::*;
+
+// =============================================================================
+// Key disorder: roaming, sliding, overlapping, resizing and recoloring keys
+// =============================================================================
+
+// Attached keys wander around their sockets along noise paths, swell and shrink,
+// shuffle their draw order (so they slide under one another) and drift in color.
+// Pair with KeyHitTestMode::Bottommost to make taps pass through to the key underneath.
+// Detached keycaps are left to the physics.
+#[derive(Resource, Debug)]
+pub struct KeyDisorder {
+	pub strength: f32,			// 0 is a normal keyboard, 1 is the full effect below.
+	pub wander_radius: f32,		// How far (world units) a key may roam from its socket.
+	pub scale_swing: f32,		// How much bigger or smaller (as a fraction) a key may get.
+	pub color_drift: f32,		// How far (0 to 1) a key's color may mix toward a wandering hue.
+	pub speed: f32,				// Multiplier on how fast keys move along their paths.
+}
+impl Default for KeyDisorder {
+	fn default() -> Self {
+		Self {
+			strength: 0.,
+			wander_radius: 140.,
+			scale_swing: 0.35,
+			color_drift: 0.6,
+			speed: 0.6,
+		}
+	}
+}
+
+// Run condition for animate_key_disorder.
+pub fn key_disorder_active(key_disorder: Res<KeyDisorder>) -> bool {
+	key_disorder.strength > 0.
+}
+
+// Each key gets its own path, seeded by where it lives on the keyboard.
+fn key_seed(socket: &KeySocket) -> f32 {
+	socket.home.x * 0.0131 + socket.home.y * 0.0173
+}
+
+pub fn animate_key_disorder(
+	time: Res<Time>,
+	key_disorder: Res<KeyDisorder>,
+	color_scheme: Res<ColorScheme>,
+	mut keycaps: Query<(&Keycap, &mut Transform, &Children), Without<Detached>>,
+	sockets: Query<&KeySocket>,
+	mut fills: Query<(&mut ShapeFill, &KeyColorRole)>,
+) {
+	let t = time.elapsed_secs() * key_disorder.speed;
+	let strength = key_disorder.strength.clamp(0., 1.);
+
+	for (keycap, mut transform, children) in &mut keycaps {
+		let Ok(socket) = sockets.get(keycap.socket) else {
+			continue;
+		};
+		let seed = key_seed(socket);
+
+		let offset = smooth_noise_2d(t, seed) * key_disorder.wander_radius * strength;
+		// Draw order wanders too, staying between the sockets and any loose keycaps.
+		let depth = (smooth_noise(t * 0.5, seed + 7.3) + 1.) * 0.5 * (DETACHED_KEYCAP_Z - KEYCAP_Z) * 0.98;
+		transform.translation = Vec3::new(socket.home.x + offset.x, socket.home.y + offset.y, KEYCAP_Z + depth);
+
+		let scale = 1. + smooth_noise(t * 0.8, seed + 3.1) * key_disorder.scale_swing * strength;
+		transform.scale = Vec3::new(scale, scale, 1.);
+
+		let hue = (smooth_noise(t * 0.3, seed + 11.9) + 1.) * 180.;
+		let drift = key_disorder.color_drift * strength;
+		for child in children.iter() {
+			if let Ok((mut fill, role)) = fills.get_mut(*child) {
+				fill.color = role.color(&color_scheme).mix(&Color::hsl(hue, 0.7, 0.5), drift);
+			}
+		}
+	}
+}
+
+// Puts every attached key back where it belongs once the disorder is switched off.
+// This runs when KeyDisorder changes (see App setup).
+pub fn restore_keys_after_disorder(
+	key_disorder: Res<KeyDisorder>,
+	color_scheme: Res<ColorScheme>,
+	mut keycaps: Query<(&Keycap, &mut Transform, &Children), Without<Detached>>,
+	sockets: Query<&KeySocket>,
+	mut fills: Query<(&mut ShapeFill, &KeyColorRole)>,
+) {
+	if key_disorder.strength > 0. {
+		return;
+	}
+
+	for (keycap, mut transform, children) in &mut keycaps {
+		let Ok(socket) = sockets.get(keycap.socket) else {
+			continue;
+		};
+		*transform = Transform::from_translation(socket.home.extend(KEYCAP_Z));
+		for child in children.iter() {
+			if let Ok((mut fill, role)) = fills.get_mut(*child) {
+				fill.color = role.color(&color_scheme);
+			}
+		}
+	}
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{
+	Resource, On,
+	Res, ResMut,
+	Time,
+};
+use rand::Rng;
+
+use crate::FeverLevel;
+use crate::audio::{AudioManager, AudioBus, Sound};
+use crate::keyboard::{KeyTap, BACKSPACE_GLYPH, RETURN_GLYPH};
+
+// =============================================================================
+// Key sounds: the click of every tap, and what it turns into
+// =============================================================================
+
+// Every tap picks a variant from its key class's pool, nudged in pitch and volume so no two
+// sound quite alike. Each pool comes in two sets, clean and unsettling, and every tap plays
+// both, crossfaded by KeySoundMorph - which creeps toward the fever stage's target, so the
+// keyboard goes wrong too slowly to notice. On top of that, each stage has a table of chances
+// for the tap to be something else entirely (a squish, a yelp, the screen smunching).
+
+// Also sent to the audio backend as a parameter, for FMOD's sake.
+const KEY_SOUND_MORPH_PARAMETER: &str = "key_morph";
+// Sides of the crossfade quieter than this aren't played at all.
+const KEY_SOUND_SILENT: f32 = 0.01;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyClass {
+	Letter,			// Anything that types a visible glyph.
+	Space,
+	Return,
+	Backspace,
+}
+impl KeyClass {
+	pub fn of(glyph: char) -> Self {
+		match glyph {
+			' ' => KeyClass::Space,
+			RETURN_GLYPH => KeyClass::Return,
+			BACKSPACE_GLYPH => KeyClass::Backspace,
+			_ => KeyClass::Letter,
+		}
+	}
+}
+
+// Sounds (paths, relative to assets/) to pick from, one at random per tap. The two sets pair up by index.
+#[derive(Clone, Debug, Default)]
+pub struct KeySoundPool {
+	pub clean: Vec<&'static str>,
+	pub unsettling: Vec<&'static str>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeySoundStage {
+	pub unsettling: f32,								// Where KeySoundMorph heads: 0 all clean, 1 all unsettling.
+	pub replacements: &'static [(f32, &'static str)],	// (Chance per tap, sound) to play instead.
+}
+impl KeySoundStage {
+	const CALM: Self = Self {
+		unsettling: 0.,
+		replacements: &[],
+	};
+}
+
+const SQUISH_SOUND_PATH: &str = "audio/sfx/keys/replacements/squish.wav";
+const YELP_SOUND_PATH: &str = "audio/sfx/keys/replacements/yelp.wav";
+const SMUNCH_SOUND_PATH: &str = "audio/sfx/keys/replacements/smunch.wav";
+
+// One entry per FeverLevel; levels past the end use the last entry.
+#[derive(Resource, Debug)]
+pub struct KeySoundSettings {
+	pub pools: HashMap<KeyClass, KeySoundPool>,
+	pub volume: f32,
+	pub volume_jitter: f32,			// Up to this much quieter, at random.
+	pub pitch_jitter: f32,			// Up to this much slower or faster (and so lower or higher), at random.
+	pub morph_per_second: f32,		// How fast KeySoundMorph moves toward the stage's target.
+	pub stages: Vec<KeySoundStage>,
+}
+impl Default for KeySoundSettings {
+	fn default() -> Self {
+		Self {
+			pools: HashMap::from([
+				(KeyClass::Letter, KeySoundPool {
+					clean: vec![
+						"audio/sfx/keys/clean/letter_1.wav",
+						"audio/sfx/keys/clean/letter_2.wav",
+						"audio/sfx/keys/clean/letter_3.wav",
+					],
+					unsettling: vec![
+						"audio/sfx/keys/unsettling/letter_1.wav",
+						"audio/sfx/keys/unsettling/letter_2.wav",
+						"audio/sfx/keys/unsettling/letter_3.wav",
+					],
+				}),
+				(KeyClass::Space, KeySoundPool {
+					clean: vec!["audio/sfx/keys/clean/space.wav"],
+					unsettling: vec!["audio/sfx/keys/unsettling/space.wav"],
+				}),
+				(KeyClass::Return, KeySoundPool {
+					clean: vec!["audio/sfx/keys/clean/return.wav"],
+					unsettling: vec!["audio/sfx/keys/unsettling/return.wav"],
+				}),
+				(KeyClass::Backspace, KeySoundPool {
+					clean: vec!["audio/sfx/keys/clean/backspace.wav"],
+					unsettling: vec!["audio/sfx/keys/unsettling/backspace.wav"],
+				}),
+			]),
+			volume: 0.6,
+			volume_jitter: 0.2,
+			pitch_jitter: 0.06,
+			morph_per_second: 0.02,
+			stages: vec![
+				KeySoundStage::CALM,
+				KeySoundStage {
+					unsettling: 0.15,
+					..KeySoundStage::CALM
+				},
+				KeySoundStage {
+					unsettling: 0.35,
+					replacements: &[(0.02, SQUISH_SOUND_PATH)],
+				},
+				KeySoundStage {
+					unsettling: 0.6,
+					replacements: &[(0.05, SQUISH_SOUND_PATH), (0.02, YELP_SOUND_PATH)],
+				},
+				KeySoundStage {
+					unsettling: 1.,
+					replacements: &[(0.1, SQUISH_SOUND_PATH), (0.05, YELP_SOUND_PATH), (0.04, SMUNCH_SOUND_PATH)],
+				},
+			],
+		}
+	}
+}
+impl KeySoundSettings {
+	pub fn stage(&self, fever_level: usize) -> KeySoundStage {
+		self.stages.get(fever_level)
+			.or(self.stages.last())
+			.copied()
+			.unwrap_or(KeySoundStage::CALM)
+	}
+}
+
+// The crossfade between the clean and unsettling sets: 0 all clean, 1 all unsettling.
+#[derive(Resource, Debug, Default)]
+pub struct KeySoundMorph(pub f32);
+
+pub fn ease_key_sound_morph(
+	time: Res<Time>,
+	fever_level: Res<FeverLevel>,
+	settings: Res<KeySoundSettings>,
+	mut morph: ResMut<KeySoundMorph>,
+	mut audio: ResMut<AudioManager>,
+) {
+	let target = settings.stage(fever_level.0).unsettling.clamp(0., 1.);
+	let max_step = settings.morph_per_second * time.delta_secs();
+	let step = (target - morph.0).clamp(-max_step, max_step);
+	if step != 0. {
+		morph.0 += step;
+		audio.set_parameter(KEY_SOUND_MORPH_PARAMETER, morph.0);
+	}
+}
+
+pub fn on_key_tap_sound(
+	event: On<KeyTap>,
+	fever_level: Res<FeverLevel>,
+	settings: Res<KeySoundSettings>,
+	morph: Res<KeySoundMorph>,
+	mut audio: ResMut<AudioManager>,
+) {
+	let mut rng = rand::rng();
+	let stage = settings.stage(fever_level.0);
+	// Clean and unsettling share the jitter and the variant, so the crossfade stays in tune with itself.
+	let volume = settings.volume * (1. - rng.random_range(0. ..=settings.volume_jitter.max(0.)));
+	let speed = 1. + rng.random_range(-settings.pitch_jitter.abs()..=settings.pitch_jitter.abs());
+
+	// At most one replacement per tap, so the table's chances are read as a running total.
+	let roll: f32 = rng.random();
+	let mut total = 0.;
+	for (chance, path) in stage.replacements {
+		total += chance;
+		if roll < total {
+			audio.play(Sound::new(*path, AudioBus::Sfx).with_volume(volume).with_speed(speed));
+			return;
+		}
+	}
+
+	let Some(pool) = settings.pools.get(&KeyClass::of(event.glyph)) else {
+		return;
+	};
+	// Pick one variant: each unsettling sample is a warped copy of the clean one at the same index.
+	let index = rng.random_range(0..pool.clean.len().max(pool.unsettling.len()).max(1));
+	for (variants, mix) in [(&pool.clean, 1. - morph.0), (&pool.unsettling, morph.0)] {
+		if mix < KEY_SOUND_SILENT || variants.is_empty() {
+			continue;
+		}
+		let path = variants[index % variants.len()];
+		audio.play(Sound::new(path, AudioBus::Sfx).with_volume(volume * mix).with_speed(speed));
+	}
+}
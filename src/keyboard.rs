@@ -0,0 +1,741 @@
+use bevy::prelude::{
+	Component, Bundle, Resource, Event, On, In,
+	Entity, Name,
+	Query, Res, Single, With, Without, Has,
+	Commands,
+	Time,
+	ButtonInput, KeyCode, MouseButton,
+	Camera, GlobalTransform, Window,
+	Transform, Visibility,
+	Text2d, TextFont, TextColor,
+	Color,
+	Vec2, Vec3, Vec4,
+	default,
+};
+use bevy::app::Propagate;
+use bevy::camera::visibility::RenderLayers;
+use bevy::ecs::system::SystemParam;
+use bevy_vector_shapes::prelude::*;
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::{
+	VIRTUAL_RESOLUTION,
+	DEFAULT_KEY_SIZE, DEFAULT_SPECIAL_KEY_SIZE, DEFAULT_MINI_SPECIAL_KEY_SIZE,
+	DEFAULT_SPACEBAR_SIZE, DEFAULT_RETURN_KEY_SIZE,
+	DEFAULT_KEY_HEIGHT, DEFAULT_KEY_SPACING, DEFAULT_KEY_CORNER_RADIUS,
+	DEFAULT_ROW_1_MARGIN, DEFAULT_ROW_2_MARGIN, DEFAULT_ROW_3_INNER_MARGIN,
+	DEFAULT_ROW_3_OUTER_MARGIN, DEFAULT_ROW_4_MARGIN,
+	DEFAULT_KEY_ROW_SPACING, DEFAULT_KEYBOARD_BOTTOM_MARGIN, DEFAULT_KEYBOARD_HEIGHT,
+};
+use crate::blink::BlinkKind;
+use crate::cleanup::LivesIn;
+use crate::color_utils::ColorScheme;
+use crate::double_vision::{KEYBOARD_RENDER_LAYER, LayerCamera};
+use crate::physics::*;
+use crate::screen_cracks::{ScreenCracks, ScreenCrackSettings};
+
+// =============================================================================
+// Key taps
+// =============================================================================
+
+// Glyphs sent for the non-printing keys that still affect the draft.
+pub const BACKSPACE_GLYPH: char = '\u{8}';
+pub const RETURN_GLYPH: char = '\n';
+
+#[derive(Event, Debug)]
+pub struct KeyTap {
+	pub glyph: char
+}
+
+// Debug builds only (see App setup).
+#[cfg(debug_assertions)]
+pub fn on_key_pressed(event: On<KeyTap>) {
+	println!("Key pressed: {:?}", event.glyph);
+	// (Typing into the draft is handled in draft.rs, and the click in key_sounds.rs.)
+}
+
+// =============================================================================
+// Layout
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyKind {
+	Glyph(char),
+	Shift,
+	Backspace,
+	Numbers,
+	Emoji,
+	Space,
+	Return,
+}
+impl KeyKind {
+	// What tapping this key types, if anything.
+	pub fn glyph(&self) -> Option<char> {
+		match self {
+			KeyKind::Glyph(glyph) => Some(*glyph),
+			KeyKind::Space => Some(' '),
+			KeyKind::Return => Some(RETURN_GLYPH),
+			KeyKind::Backspace => Some(BACKSPACE_GLYPH),
+			KeyKind::Shift | KeyKind::Numbers | KeyKind::Emoji => None,
+		}
+	}
+
+	pub fn label(&self) -> String {
+		match self {
+			KeyKind::Glyph(glyph) => glyph.to_uppercase().to_string(),
+			KeyKind::Shift => String::from("⇧"),
+			KeyKind::Backspace => String::from("⌫"),
+			KeyKind::Numbers => String::from("123"),
+			KeyKind::Emoji => String::from("😊"),
+			KeyKind::Space => String::from("space"),
+			KeyKind::Return => String::from("return"),
+		}
+	}
+
+	// Maps a physical keyboard key to the on-screen key it stands in for.
+	pub fn from_key_code(key_code: KeyCode) -> Option<Self> {
+		let glyph = match key_code {
+			KeyCode::KeyA => 'a', KeyCode::KeyB => 'b', KeyCode::KeyC => 'c', KeyCode::KeyD => 'd',
+			KeyCode::KeyE => 'e', KeyCode::KeyF => 'f', KeyCode::KeyG => 'g', KeyCode::KeyH => 'h',
+			KeyCode::KeyI => 'i', KeyCode::KeyJ => 'j', KeyCode::KeyK => 'k', KeyCode::KeyL => 'l',
+			KeyCode::KeyM => 'm', KeyCode::KeyN => 'n', KeyCode::KeyO => 'o', KeyCode::KeyP => 'p',
+			KeyCode::KeyQ => 'q', KeyCode::KeyR => 'r', KeyCode::KeyS => 's', KeyCode::KeyT => 't',
+			KeyCode::KeyU => 'u', KeyCode::KeyV => 'v', KeyCode::KeyW => 'w', KeyCode::KeyX => 'x',
+			KeyCode::KeyY => 'y', KeyCode::KeyZ => 'z',
+			KeyCode::Space => return Some(KeyKind::Space),
+			KeyCode::Enter => return Some(KeyKind::Return),
+			KeyCode::Backspace => return Some(KeyKind::Backspace),
+			KeyCode::ShiftLeft | KeyCode::ShiftRight => return Some(KeyKind::Shift),
+			_ => return None,
+		};
+		Some(KeyKind::Glyph(glyph))
+	}
+}
+
+// Whether some key on the default layout types this glyph (ignoring case, as there's no caps lock).
+pub fn can_type(glyph: char) -> bool {
+	default_key_layout().iter()
+		.filter_map(|spec| spec.kind.glyph())
+		.any(|key_glyph| key_glyph.to_lowercase().eq(glyph.to_lowercase()))
+}
+
+// Where a key sits when it is attached, in world coordinates (origin at screen center).
+#[derive(Clone, Copy, Debug)]
+pub struct KeySpec {
+	pub kind: KeyKind,
+	pub center: Vec2,
+	pub size: Vec2,
+}
+
+// Builds the layout sketched out in main.rs (see notes/keyboard-layout-calculations.txt for the widths).
+pub fn default_key_layout() -> Vec<KeySpec> {
+	let left = -(VIRTUAL_RESOLUTION.x as f32) * 0.5;
+	let right = -left;
+	let bottom = -(VIRTUAL_RESOLUTION.y as f32) * 0.5;
+	let row_y = |row_from_bottom: usize| {
+		bottom + DEFAULT_KEYBOARD_BOTTOM_MARGIN + DEFAULT_KEY_HEIGHT * 0.5
+			+ row_from_bottom as f32 * (DEFAULT_KEY_HEIGHT + DEFAULT_KEY_ROW_SPACING)
+	};
+
+	let mut layout = Vec::new();
+
+	// Rows of plain glyph keys, evenly spaced from a left margin.
+	let mut glyph_row = |glyphs: &str, first_left_edge: f32, y: f32| {
+		for (i, glyph) in glyphs.chars().enumerate() {
+			let x = first_left_edge + DEFAULT_KEY_SIZE.x * 0.5 + i as f32 * (DEFAULT_KEY_SIZE.x + DEFAULT_KEY_SPACING);
+			layout.push(KeySpec { kind: KeyKind::Glyph(glyph), center: Vec2::new(x, y), size: DEFAULT_KEY_SIZE });
+		}
+	};
+	glyph_row("qwertyuiop", left + DEFAULT_ROW_1_MARGIN, row_y(3));
+	glyph_row("asdfghjkl", left + DEFAULT_ROW_2_MARGIN, row_y(2));
+	glyph_row(
+		"zxcvbnm",
+		left + DEFAULT_ROW_3_OUTER_MARGIN + DEFAULT_SPECIAL_KEY_SIZE.x + DEFAULT_ROW_3_INNER_MARGIN,
+		row_y(1),
+	);
+
+	// Row 3 specials.
+	layout.push(KeySpec {
+		kind: KeyKind::Shift,
+		center: Vec2::new(left + DEFAULT_ROW_3_OUTER_MARGIN + DEFAULT_SPECIAL_KEY_SIZE.x * 0.5, row_y(1)),
+		size: DEFAULT_SPECIAL_KEY_SIZE,
+	});
+	layout.push(KeySpec {
+		kind: KeyKind::Backspace,
+		center: Vec2::new(right - DEFAULT_ROW_3_OUTER_MARGIN - DEFAULT_SPECIAL_KEY_SIZE.x * 0.5, row_y(1)),
+		size: DEFAULT_SPECIAL_KEY_SIZE,
+	});
+
+	// Row 4: 123, 😊, space, return (left to right).
+	let mut x = left + DEFAULT_ROW_4_MARGIN;
+	for (kind, size) in [
+		(KeyKind::Numbers, DEFAULT_MINI_SPECIAL_KEY_SIZE),
+		(KeyKind::Emoji, DEFAULT_MINI_SPECIAL_KEY_SIZE),
+		(KeyKind::Space, DEFAULT_SPACEBAR_SIZE),
+		(KeyKind::Return, DEFAULT_RETURN_KEY_SIZE),
+	] {
+		layout.push(KeySpec { kind, center: Vec2::new(x + size.x * 0.5, row_y(0)), size });
+		x += size.x + DEFAULT_KEY_SPACING;
+	}
+
+	layout
+}
+
+// =============================================================================
+// Keycaps and sockets
+// =============================================================================
+
+// Draw order within the keyboard. The keyboard is drawn on its own layer and composited as a single
+// quad (see double_vision.rs), so these only order its parts against each other: even a loose legend
+// can't come out in front of anything else, such as the ghost text.
+pub const KEYBOARD_Z: f32 = 1.;
+pub const SOCKET_Z: f32 = 2.;
+pub const KEYCAP_Z: f32 = 3.;			// Attached keycaps may be shuffled anywhere below DETACHED_KEYCAP_Z.
+pub const DETACHED_KEYCAP_Z: f32 = 4.;
+pub const HELD_KEYCAP_Z: f32 = 5.;
+pub const LOOSE_LEGEND_Z: f32 = 6.;
+// How far in front of its keycap an attached legend is drawn.
+const LEGEND_Z_OFFSET: f32 = 0.005;
+
+// How much of the key footprint is left behind as a tappable socket when the keycap pops off.
+const SOCKET_SCALE: f32 = 0.35;
+// How close (in world units) a dropped keycap must be to its socket to snap back on.
+const SOCKET_SNAP_DISTANCE: f32 = 60.;
+
+const KEY_LABEL_FONT_SIZE: f32 = 48.;
+
+// A key's identity and home position. Lives on the socket, which never moves.
+#[derive(Component, Debug)]
+pub struct KeySocket {
+	pub kind: KeyKind,
+	pub home: Vec2,
+}
+
+// The part of the key that can pop off. Points back at its socket.
+#[derive(Component, Debug)]
+pub struct Keycap {
+	pub kind: KeyKind,
+	pub socket: Entity,
+	pub size: Vec2,
+}
+
+// Marks a keycap that has come off its socket (and is now a physics body or being carried).
+#[derive(Component)]
+pub struct Detached;
+
+// The keyboard's background panel.
+#[derive(Component)]
+pub struct KeyboardBase;
+
+// A detached keycap currently being dragged by the pointer.
+#[derive(Component)]
+pub struct Held {
+	pub grab_offset: Vec2,
+	pub last_pointer: Vec2,
+	pub pointer_velocity: Vec2,
+}
+
+// Half extents of the tappable area (before scaling), centered on the entity's translation.
+#[derive(Component, Debug)]
+pub struct KeyHitArea(pub Vec2);
+impl KeyHitArea {
+	pub fn contains(&self, transform: &Transform, point: Vec2) -> bool {
+		let half_size = self.0 * transform.scale.truncate().abs();
+		let delta = (point - transform.translation.truncate()).abs();
+		delta.x <= half_size.x && delta.y <= half_size.y
+	}
+}
+
+// How a tap resolves when attached keys overlap.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyHitTestMode {
+	#[default]
+	Topmost,
+	Bottommost,		// Taps pass through to whichever key is underneath.
+}
+
+// Resolves a point on the keyboard to the key it types, honouring KeyHitTestMode.
+// If no attached keycap is there, an empty socket still counts.
+#[derive(SystemParam)]
+pub struct KeyHitTester<'w, 's> {
+	mode: Res<'w, KeyHitTestMode>,
+	cracks: Res<'w, ScreenCracks>,
+	crack_settings: Res<'w, ScreenCrackSettings>,
+	keycaps: Query<'w, 's, (&'static Keycap, &'static Transform, &'static KeyHitArea), Without<Detached>>,
+	sockets: Query<'w, 's, (&'static KeySocket, &'static Transform, &'static KeyHitArea)>,
+}
+impl KeyHitTester<'_, '_> {
+	pub fn kind_at(&self, point: Vec2) -> Option<KeyKind> {
+		let point = self.cracks.distort(point, &self.crack_settings);
+		let hits = self.keycaps.iter()
+			.filter(|(_, transform, hit_area)| hit_area.contains(transform, point));
+		let by_z = |a: &(&Keycap, &Transform, &KeyHitArea), b: &(&Keycap, &Transform, &KeyHitArea)| {
+			a.1.translation.z.total_cmp(&b.1.translation.z)
+		};
+		let hit = match *self.mode {
+			KeyHitTestMode::Topmost => hits.max_by(by_z),
+			KeyHitTestMode::Bottommost => hits.min_by(by_z),
+		};
+		if let Some((keycap, _, _)) = hit {
+			return Some(keycap.kind);
+		}
+
+		self.sockets.iter()
+			.find(|(_, transform, hit_area)| hit_area.contains(transform, point))
+			.map(|(socket, _, _)| socket.kind)
+	}
+}
+
+// Which ColorScheme entry a key shape is drawn with.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyColorRole {
+	Key,
+	Caps,
+	Bksp,
+	Socket,
+	Keyboard,
+}
+impl KeyColorRole {
+	fn for_kind(kind: KeyKind) -> Self {
+		match kind {
+			KeyKind::Shift => KeyColorRole::Caps,
+			KeyKind::Backspace | KeyKind::Numbers | KeyKind::Return => KeyColorRole::Bksp,
+			_ => KeyColorRole::Key,
+		}
+	}
+
+	pub fn color(&self, color_scheme: &ColorScheme) -> Color {
+		match self {
+			KeyColorRole::Key => color_scheme.key_color,
+			KeyColorRole::Caps => color_scheme.key_color_caps,
+			KeyColorRole::Bksp => color_scheme.key_color_bksp,
+			KeyColorRole::Socket => color_scheme.key_color_bksp,
+			KeyColorRole::Keyboard => color_scheme.keyboard_color,
+		}
+	}
+}
+
+// What typing a detached key's letter on the physical keyboard does until the keycap is replaced.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DetachedKeyBehavior {
+	#[default]
+	Nothing,
+	WrongGlyph,
+}
+
+fn key_shape(color: Color, size: Vec2) -> impl Bundle {
+	ShapeBundle::rect(
+		&ShapeConfig {
+			color,
+			corner_radii: Vec4::splat(DEFAULT_KEY_CORNER_RADIUS),
+			..ShapeConfig::default_2d()
+		},
+		size,
+	)
+}
+
+// Spawns the keyboard background plus a socket and keycap per key in the layout.
+// The keyboard is part of the room, so it stays through pausing and goes when the game does.
+// All of it is drawn on KEYBOARD_RENDER_LAYER, so double vision can get at it (see double_vision.rs).
+pub fn spawn_keyboard(commands: &mut Commands, color_scheme: &ColorScheme) {
+	let bottom = -(VIRTUAL_RESOLUTION.y as f32) * 0.5;
+	let keyboard_size = Vec2::new(VIRTUAL_RESOLUTION.x as f32, DEFAULT_KEYBOARD_HEIGHT);
+	commands.spawn((
+		Name::new("Keyboard"),
+		LivesIn::game(),
+		KeyboardBase,
+		Propagate(RenderLayers::layer(KEYBOARD_RENDER_LAYER)),
+		Transform::from_xyz(0., bottom + keyboard_size.y * 0.5, KEYBOARD_Z),
+		Visibility::default(),
+	)).with_child((
+		key_shape(KeyColorRole::Keyboard.color(color_scheme), keyboard_size),
+		KeyColorRole::Keyboard,
+	));
+
+	for spec in default_key_layout() {
+		let socket_size = spec.size * SOCKET_SCALE;
+		let socket = commands.spawn((
+			Name::new(format!("KeySocket {}", spec.kind.label())),
+			LivesIn::game(),
+			Transform::from_translation(spec.center.extend(SOCKET_Z)),
+			Visibility::default(),
+			KeyHitArea(socket_size * 0.5),
+			Propagate(RenderLayers::layer(KEYBOARD_RENDER_LAYER)),
+		)).with_child((
+			key_shape(KeyColorRole::Socket.color(color_scheme), socket_size),
+			KeyColorRole::Socket,
+		)).id();
+
+		let role = KeyColorRole::for_kind(spec.kind);
+		let keycap = commands.spawn((
+			Name::new(format!("Keycap {}", spec.kind.label())),
+			LivesIn::game(),
+			Keycap { kind: spec.kind, socket, size: spec.size },
+			Transform::from_translation(spec.center.extend(KEYCAP_Z)),
+			Visibility::default(),
+			KeyHitArea(spec.size * 0.5),
+			Propagate(RenderLayers::layer(KEYBOARD_RENDER_LAYER)),
+		)).with_child((
+			key_shape(role.color(color_scheme), spec.size),
+			role,
+		)).id();
+
+		// The legend is its own entity (not a child) so it can wander off without taking the key with it.
+		commands.spawn((
+			Name::new(format!("KeyLegend {}", spec.kind.label())),
+			LivesIn::game(),
+			KeyLegend { keycap, home_keycap: keycap },
+			Text2d::new(spec.kind.label()),
+			TextFont { font_size: KEY_LABEL_FONT_SIZE, ..default() },
+			TextColor(color_scheme.key_text_color),
+			Propagate(RenderLayers::layer(KEYBOARD_RENDER_LAYER)),
+			Transform::from_translation(spec.center.extend(KEYCAP_Z + LEGEND_Z_OFFSET)),
+		));
+
+		commands.entity(socket).insert(KeySocket { kind: spec.kind, home: spec.center });
+	}
+}
+
+// Turn an attached keycap into a physics body flung with the given velocity.
+pub fn detach_keycap(commands: &mut Commands, keycap: Entity, keycap_info: &Keycap, transform: &Transform, velocity: Vec2, spin: f32) {
+	let mut transform = *transform;
+	transform.translation.z = DETACHED_KEYCAP_Z;
+	commands.entity(keycap).insert((
+		Detached,
+		transform,
+		PhysicsBodyBundle::from_transform(&transform, velocity, spin),
+		Gravity::default(),
+		Damping { linear: 0.3, angular: 1.5 },
+		BoundsCollider { half_size: keycap_info.size * 0.5, restitution: 0.35 },
+		CollideWithScreen,
+	));
+}
+
+fn remove_keycap_physics(commands: &mut Commands, keycap: Entity) {
+	commands.entity(keycap).remove::<(
+		PhysicsBodyBundle,
+		Gravity,
+		Damping,
+		BoundsCollider,
+		CollideWithScreen,
+	)>();
+}
+
+// Snap a keycap back onto its socket.
+pub fn reattach_keycap(commands: &mut Commands, keycap: Entity, home: Vec2) {
+	remove_keycap_physics(commands, keycap);
+	commands.entity(keycap)
+		.remove::<(Detached, Held)>()
+		.insert(Transform::from_translation(home.extend(KEYCAP_Z)));
+}
+
+// Trigger to pop this many random keycaps off the keyboard.
+#[derive(Event, Debug)]
+pub struct PopKeycaps(pub usize);
+
+// Trigger to pop every remaining keycap off at once.
+#[derive(Event, Debug)]
+pub struct EruptKeycaps;
+
+pub fn on_pop_keycaps(
+	event: On<PopKeycaps>,
+	mut commands: Commands,
+	keycaps: Query<(Entity, &Keycap, &Transform), Without<Detached>>,
+) {
+	let mut rng = rand::rng();
+	let attached: Vec<_> = keycaps.iter().collect();
+	for (keycap, keycap_info, transform) in attached.choose_multiple(&mut rng, event.0) {
+		let velocity = Vec2::new(rng.random_range(-250. ..250.), rng.random_range(500. ..900.));
+		let spin = rng.random_range(-6. ..6.);
+		detach_keycap(&mut commands, *keycap, keycap_info, transform, velocity, spin);
+	}
+}
+
+pub fn on_erupt_keycaps(
+	_event: On<EruptKeycaps>,
+	mut commands: Commands,
+	keycaps: Query<(Entity, &Keycap, &Transform), Without<Detached>>,
+) {
+	let mut rng = rand::rng();
+	for (keycap, keycap_info, transform) in &keycaps {
+		// Fling outward from the middle of the keyboard, hard enough to clear the screen top.
+		let outward = transform.translation.x.signum() * rng.random_range(100. ..600.);
+		let velocity = Vec2::new(outward, rng.random_range(1400. ..2600.));
+		let spin = rng.random_range(-12. ..12.);
+		detach_keycap(&mut commands, keycap, keycap_info, transform, velocity, spin);
+	}
+}
+
+// =============================================================================
+// Input
+// =============================================================================
+
+pub fn cursor_world_position(window: &Window, camera: &Camera, camera_transform: &GlobalTransform) -> Option<Vec2> {
+	let cursor = window.cursor_position()?;
+	camera.viewport_to_world_2d(camera_transform, cursor).ok()
+}
+
+// Physical keyboard typing. A key whose keycap has popped off types according to DetachedKeyBehavior.
+pub fn type_from_physical_keyboard(
+	mut commands: Commands,
+	keyboard_input: Res<ButtonInput<KeyCode>>,
+	detached_key_behavior: Res<DetachedKeyBehavior>,
+	keycaps: Query<(&Keycap, Has<Detached>)>,
+) {
+	for key_code in keyboard_input.get_just_pressed() {
+		let Some(kind) = KeyKind::from_key_code(*key_code) else {
+			continue;
+		};
+		let detached = keycaps.iter().any(|(keycap, detached)| keycap.kind == kind && detached);
+
+		let glyph = if !detached {
+			kind.glyph()
+		} else {
+			match *detached_key_behavior {
+				DetachedKeyBehavior::Nothing => None,
+				DetachedKeyBehavior::WrongGlyph => {
+					// Some other letter that is still on the board.
+					let others: Vec<char> = keycaps.iter()
+						.filter(|(keycap, detached)| !detached && keycap.kind != kind)
+						.filter_map(|(keycap, _)| match keycap.kind { KeyKind::Glyph(glyph) => Some(glyph), _ => None })
+						.collect();
+					others.choose(&mut rand::rng()).copied()
+				}
+			}
+		};
+
+		if let Some(glyph) = glyph {
+			commands.trigger(KeyTap { glyph });
+		}
+	}
+}
+
+// Pointer presses: pick up detached keycaps, otherwise tap whatever key is under the pointer.
+pub fn handle_key_pointer_press(
+	mut commands: Commands,
+	mouse_input: Res<ButtonInput<MouseButton>>,
+	window: Single<&Window>,
+	camera: Single<(&Camera, &GlobalTransform), Without<LayerCamera>>,
+	detached_keycaps: Query<(Entity, &Transform, &KeyHitArea), With<Detached>>,
+	hit_tester: KeyHitTester,
+) {
+	if !mouse_input.just_pressed(MouseButton::Left) {
+		return;
+	}
+	let (camera, camera_transform) = *camera;
+	let Some(pointer) = cursor_world_position(&window, camera, camera_transform) else {
+		return;
+	};
+
+	// Loose keycaps always sit above the keyboard, so they get first dibs (topmost first).
+	let loose = detached_keycaps.iter()
+		.filter(|(_, transform, hit_area)| hit_area.contains(transform, pointer))
+		.max_by(|a, b| a.1.translation.z.total_cmp(&b.1.translation.z));
+
+	if let Some((entity, transform, _)) = loose {
+		let mut transform = *transform;
+		transform.translation.z = HELD_KEYCAP_Z;
+		remove_keycap_physics(&mut commands, entity);
+		commands.entity(entity).insert((
+			transform,
+			Held {
+				grab_offset: transform.translation.truncate() - pointer,
+				last_pointer: pointer,
+				pointer_velocity: Vec2::ZERO,
+			},
+		));
+		return;
+	}
+
+	if let Some(glyph) = hit_tester.kind_at(pointer).and_then(|kind| kind.glyph()) {
+		commands.trigger(KeyTap { glyph });
+	}
+}
+
+// Carry held keycaps with the pointer, and drop them (onto their socket, if close enough) on release.
+pub fn drag_held_keycaps(
+	mut commands: Commands,
+	time: Res<Time>,
+	mouse_input: Res<ButtonInput<MouseButton>>,
+	window: Single<&Window>,
+	camera: Single<(&Camera, &GlobalTransform), Without<LayerCamera>>,
+	mut held: Query<(Entity, &Keycap, &mut Transform, &mut Held)>,
+	sockets: Query<&KeySocket>,
+) {
+	let (camera, camera_transform) = *camera;
+	let pointer = cursor_world_position(&window, camera, camera_transform);
+
+	for (entity, keycap, mut transform, mut held) in &mut held {
+		if let Some(pointer) = pointer {
+			let dt = time.delta_secs().max(f32::EPSILON);
+			held.pointer_velocity = (pointer - held.last_pointer) / dt;
+			held.last_pointer = pointer;
+			let target = pointer + held.grab_offset;
+			transform.translation = Vec3::new(target.x, target.y, HELD_KEYCAP_Z);
+		}
+
+		if mouse_input.just_released(MouseButton::Left) {
+			let Ok(socket) = sockets.get(keycap.socket) else {
+				// No socket to go back to: just drop it.
+				commands.entity(entity).remove::<Held>();
+				detach_keycap(&mut commands, entity, keycap, &transform, held.pointer_velocity, 0.);
+				continue;
+			};
+			if transform.translation.truncate().distance(socket.home) <= SOCKET_SNAP_DISTANCE {
+				reattach_keycap(&mut commands, entity, socket.home);
+			} else {
+				// Let go mid-air: it falls (or is thrown) again.
+				transform.translation.z = DETACHED_KEYCAP_Z;
+				commands.entity(entity).remove::<Held>();
+				detach_keycap(&mut commands, entity, keycap, &transform, held.pointer_velocity, 0.);
+			}
+		}
+	}
+}
+
+// This runs when ColorScheme changes (see App setup).
+pub fn update_key_colors_on_color_scheme_change(
+	color_scheme: Res<ColorScheme>,
+	mut shapes: Query<(&mut ShapeFill, &KeyColorRole)>,
+	mut legends: Query<&mut TextColor, With<KeyLegend>>,
+) {
+	for (mut fill, role) in &mut shapes {
+		fill.color = role.color(&color_scheme);
+	}
+	for mut legend in &mut legends {
+		legend.0 = color_scheme.key_text_color;
+	}
+}
+
+// =============================================================================
+// Key legends
+// =============================================================================
+
+// The printed glyph on a key. It rides along on `keycap`, which starts out as (and can be
+// restored to) `home_keycap` but can be swapped to another key's cap - the key still types
+// its own character, whatever it says on top.
+#[derive(Component, Debug)]
+pub struct KeyLegend {
+	pub keycap: Entity,
+	pub home_keycap: Entity,
+}
+
+// Marks a legend that has come off its keycap and is moving under its own physics.
+#[derive(Component)]
+pub struct LooseLegend;
+
+// Triggers for legend mischief. Counts are how many legends (or pairs, for swaps) to affect.
+#[derive(Event, Debug)]
+pub struct DriftLegends(pub usize);
+#[derive(Event, Debug)]
+pub struct DropLegends(pub usize);
+#[derive(Event, Debug)]
+pub struct SwapLegends(pub usize);
+// Puts every legend back on its own keycap.
+#[derive(Event, Debug)]
+pub struct RestoreLegends;
+
+// Runs in PostUpdate (before transform propagation), once keycaps have moved for the frame.
+pub fn follow_keycap_legends(
+	mut legends: Query<(&KeyLegend, &mut Transform), (Without<LooseLegend>, Without<Keycap>)>,
+	keycaps: Query<&Transform, With<Keycap>>,
+) {
+	for (legend, mut transform) in &mut legends {
+		if let Ok(keycap_transform) = keycaps.get(legend.keycap) {
+			*transform = *keycap_transform;
+			transform.translation.z += LEGEND_Z_OFFSET;
+		}
+	}
+}
+
+fn loosen_legend(commands: &mut Commands, legend: Entity, transform: &Transform, velocity: Vec2, spin: f32) {
+	let mut transform = *transform;
+	transform.translation.z = LOOSE_LEGEND_Z;
+	commands.entity(legend).insert((
+		LooseLegend,
+		transform,
+		PhysicsBodyBundle::from_transform(&transform, velocity, spin),
+		BoundsCollider { half_size: Vec2::splat(KEY_LABEL_FONT_SIZE * 0.4), restitution: 0.5 },
+		CollideWithScreen,
+	));
+}
+
+pub fn on_drift_legends(
+	event: On<DriftLegends>,
+	mut commands: Commands,
+	legends: Query<(Entity, &Transform), (With<KeyLegend>, Without<LooseLegend>)>,
+) {
+	let mut rng = rand::rng();
+	let attached: Vec<_> = legends.iter().collect();
+	for (legend, transform) in attached.choose_multiple(&mut rng, event.0) {
+		// A lazy nudge in any direction; damping brings it to rest somewhere nearby.
+		let velocity = Vec2::from_angle(rng.random_range(0. ..std::f32::consts::TAU)) * rng.random_range(40. ..160.);
+		let spin = rng.random_range(-0.8..0.8);
+		loosen_legend(&mut commands, *legend, transform, velocity, spin);
+		commands.entity(*legend).insert(Damping { linear: 0.4, angular: 0.6 });
+	}
+}
+
+pub fn on_drop_legends(
+	event: On<DropLegends>,
+	mut commands: Commands,
+	legends: Query<(Entity, &Transform), (With<KeyLegend>, Without<LooseLegend>)>,
+) {
+	let mut rng = rand::rng();
+	let attached: Vec<_> = legends.iter().collect();
+	for (legend, transform) in attached.choose_multiple(&mut rng, event.0) {
+		let velocity = Vec2::new(rng.random_range(-60. ..60.), rng.random_range(0. ..200.));
+		let spin = rng.random_range(-4. ..4.);
+		loosen_legend(&mut commands, *legend, transform, velocity, spin);
+		commands.entity(*legend).insert((Gravity::default(), Damping { linear: 0.1, angular: 1. }));
+	}
+}
+
+pub fn on_swap_legends(
+	event: On<SwapLegends>,
+	mut legends: Query<&mut KeyLegend, Without<LooseLegend>>,
+) {
+	let mut rng = rand::rng();
+	for _ in 0..event.0 {
+		let attached: Vec<Entity> = legends.iter().map(|legend| legend.keycap).collect();
+		let picked: Vec<Entity> = attached.choose_multiple(&mut rng, 2).copied().collect();
+		let [a, b] = picked[..] else {
+			return;
+		};
+		for mut legend in &mut legends {
+			if legend.keycap == a {
+				legend.keycap = b;
+			} else if legend.keycap == b {
+				legend.keycap = a;
+			}
+		}
+	}
+}
+
+pub fn on_restore_legends(
+	_event: On<RestoreLegends>,
+	mut commands: Commands,
+	mut legends: Query<(Entity, &mut KeyLegend)>,
+) {
+	for (entity, mut legend) in &mut legends {
+		legend.keycap = legend.home_keycap;
+		commands.entity(entity).remove::<(
+			LooseLegend,
+			PhysicsBodyBundle,
+			Gravity,
+			Damping,
+			BoundsCollider,
+			CollideWithScreen,
+		)>();
+	}
+}
+
+// Blink reaction: a deliberate blink sets the legends straight again.
+pub fn restore_legends_on_blink(
+	In(kind): In<BlinkKind>,
+	mut commands: Commands,
+) {
+	if kind == BlinkKind::Voluntary {
+		commands.trigger(RestoreLegends);
+	}
+}
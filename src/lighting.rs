@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{
+	Component, Resource, Event, On,
+	Name,
+	Query, Res, ResMut, With,
+	Commands,
+	Time, Transform, Visibility,
+	Color, Vec3,
+};
+use bevy::color::Alpha;
+use bevy_vector_shapes::prelude::*;
+use rand::Rng;
+
+use crate::FeverLevel;
+use crate::beat_clock::Beat;
+use crate::cleanup::{Cleanup, Quit};
+use crate::power_cycle::{PowerOut, PowerRestored};
+
+// =============================================================================
+// Lighting: the room around the phone going dark, and the lights flickering
+// =============================================================================
+
+// The overlay is a vignette of dark rings around a glowing circle (the light the phone itself gives off).
+// Effects don't touch it directly: each one files a LightingRequest under its own name and
+// withdraws it when done. The darkest request wins, the smallest glow wins, and the flicker comes
+// from whichever request has the highest priority. Every flash is then limited by Photosensitivity.
+
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum FlickerPattern {
+	#[default]
+	Steady,
+	Random { flashes_per_second: f32, depth: f32 },			// Flashes at random moments, on average this often.
+	Rhythmic { hz: f32, duty: f32, depth: f32 },			// Regular flashes; duty is the fraction of each cycle spent dipped.
+	OnBeat { depth: f32 },									// A flash on every FlickerBeat (see below).
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LightingRequest {
+	pub darkness: f32,			// 0 is a lit room, 1 is pitch black (outside the phone's glow).
+	pub glow_radius: f32,		// How far (world units, from screen center) the phone lights things up.
+	pub flicker: FlickerPattern,
+	pub priority: i32,			// Decides whose flicker pattern plays when several are requested.
+}
+impl Default for LightingRequest {
+	fn default() -> Self {
+		Self {
+			darkness: 0.,
+			glow_radius: DEFAULT_GLOW_RADIUS,
+			flicker: FlickerPattern::Steady,
+			priority: 0,
+		}
+	}
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct LightingRequests(HashMap<&'static str, LightingRequest>);
+impl LightingRequests {
+	pub fn request(&mut self, effect: &'static str, request: LightingRequest) {
+		self.0.insert(effect, request);
+	}
+
+	pub fn release(&mut self, effect: &'static str) {
+		self.0.remove(effect);
+	}
+
+	// Everything requested, boiled down to one lighting state.
+	pub fn combined(&self) -> LightingRequest {
+		let darkness = self.0.values().map(|request| request.darkness).fold(0., f32::max);
+		let glow_radius = self.0.values().map(|request| request.glow_radius).fold(DEFAULT_GLOW_RADIUS, f32::min);
+		let flicker = self.0.values()
+			.filter(|request| request.flicker != FlickerPattern::Steady)
+			.max_by_key(|request| request.priority)
+			.map(|request| request.flicker)
+			.unwrap_or_default();
+		LightingRequest { darkness, glow_radius, flicker, priority: 0 }
+	}
+}
+
+// Caps how often (and how hard) the lights may flash, whatever the effects ask for.
+#[derive(Resource, Debug)]
+pub struct Photosensitivity {
+	pub max_flashes_per_second: f32,
+	pub max_flash_depth: f32,
+}
+impl Default for Photosensitivity {
+	fn default() -> Self {
+		// Stays within the common three-flashes-a-second guideline.
+		Self {
+			max_flashes_per_second: 3.,
+			max_flash_depth: 1.,
+		}
+	}
+}
+
+// Trigger for FlickerPattern::OnBeat (e.g. from the music's beat).
+#[derive(Event, Debug)]
+pub struct FlickerBeat;
+
+#[derive(Resource, Debug, Default)]
+pub struct LightingState {
+	pub flash_remaining: f32,		// Seconds left on the current flash.
+	pub flash_depth: f32,
+	pub since_last_flash: f32,
+	pub rhythm_phase: f32,
+	pub beat_pending: bool,
+}
+impl LightingState {
+	// Starts a flash if the photosensitivity cap allows one yet.
+	fn try_flash(&mut self, seconds: f32, depth: f32, photosensitivity: &Photosensitivity) {
+		let min_gap = 1. / photosensitivity.max_flashes_per_second.max(f32::EPSILON);
+		if self.since_last_flash < min_gap || self.flash_remaining > 0. {
+			return;
+		}
+		// The flash itself must not eat the whole gap, or back-to-back flashes merge into a strobe.
+		self.flash_remaining = seconds.min(min_gap * 0.5);
+		self.flash_depth = depth.clamp(0., photosensitivity.max_flash_depth);
+		self.since_last_flash = 0.;
+	}
+}
+
+const DEFAULT_GLOW_RADIUS: f32 = 900.;
+const LIGHTING_Z: f32 = 35.;			// Over the cracks; under the eyelids and power cycle.
+// The dark fades in over this fraction of the glow radius, in this many rings.
+const GLOW_FEATHER: f32 = 0.6;
+const GLOW_FEATHER_RINGS: usize = 10;
+// The last ring reaches this many glow radii out: far enough to cover the corners at any sensible radius.
+const DARKNESS_REACH: f32 = 12.;
+const FLASH_SECONDS: f32 = 0.09;
+
+// How dark one ring of the vignette is relative to the full darkness (0 near the glow, 1 past the feather).
+#[derive(Component, Debug)]
+pub struct LightingRing(pub f32);
+
+#[derive(Component)]
+pub struct LightingOverlay;
+
+// Rings are laid out for a glow radius of 1; the overlay is scaled to the actual radius.
+pub fn spawn_lighting_overlay(mut commands: Commands) {
+	commands.spawn((
+		Name::new("LightingOverlay"),
+		Cleanup::<Quit>::new(),
+		LightingOverlay,
+		Transform::from_xyz(0., 0., LIGHTING_Z).with_scale(Vec3::splat(DEFAULT_GLOW_RADIUS)),
+		Visibility::default(),
+	)).with_children(|parent| {
+		let ring_width = GLOW_FEATHER / GLOW_FEATHER_RINGS as f32;
+		let mut rings: Vec<(f32, f32, f32)> = (0..GLOW_FEATHER_RINGS)
+			.map(|ring| {
+				let outer = 1. + (ring + 1) as f32 * ring_width;
+				let ramp = (ring + 1) as f32 / GLOW_FEATHER_RINGS as f32;
+				(outer, ring_width, ramp * ramp)
+			})
+			.collect();
+		rings.push((DARKNESS_REACH, DARKNESS_REACH - 1. - GLOW_FEATHER, 1.));
+
+		// Radius is each ring's outer edge; thickness reaches inwards from it.
+		for (outer, thickness, ramp) in rings {
+			parent.spawn((
+				Name::new("LightingRing"),
+				LightingRing(ramp),
+				ShapeBundle::circle(
+					&ShapeConfig {
+						color: Color::BLACK.with_alpha(0.),
+						hollow: true,
+						thickness,
+						..ShapeConfig::default_2d()
+					},
+					outer,
+				),
+			));
+		}
+	});
+}
+
+pub fn on_flicker_beat(
+	_event: On<FlickerBeat>,
+	mut lighting_state: ResMut<LightingState>,
+) {
+	lighting_state.beat_pending = true;
+}
+
+// The music's beat (see BeatClock) counts as a FlickerBeat too.
+pub fn on_music_beat_flicker(
+	_event: On<Beat>,
+	mut lighting_state: ResMut<LightingState>,
+) {
+	lighting_state.beat_pending = true;
+}
+
+pub fn update_lighting(
+	time: Res<Time>,
+	requests: Res<LightingRequests>,
+	photosensitivity: Res<Photosensitivity>,
+	mut lighting_state: ResMut<LightingState>,
+	mut overlays: Query<&mut Transform, With<LightingOverlay>>,
+	mut rings: Query<(&LightingRing, &mut ShapeFill)>,
+) {
+	let dt = time.delta_secs();
+	let lighting = requests.combined();
+
+	lighting_state.since_last_flash += dt;
+	lighting_state.flash_remaining = (lighting_state.flash_remaining - dt).max(0.);
+	let beat = std::mem::take(&mut lighting_state.beat_pending);
+
+	match lighting.flicker {
+		FlickerPattern::Steady => {}
+		FlickerPattern::Random { flashes_per_second, depth } => {
+			if rand::rng().random_bool((flashes_per_second * dt).clamp(0., 1.) as f64) {
+				let seconds = rand::rng().random_range(FLASH_SECONDS * 0.5..FLASH_SECONDS * 2.);
+				lighting_state.try_flash(seconds, depth, &photosensitivity);
+			}
+		}
+		FlickerPattern::Rhythmic { hz, duty, depth } => {
+			let previous_phase = lighting_state.rhythm_phase;
+			lighting_state.rhythm_phase = (previous_phase + dt * hz).fract();
+			if lighting_state.rhythm_phase < previous_phase {
+				lighting_state.try_flash(duty.clamp(0., 1.) / hz.max(f32::EPSILON), depth, &photosensitivity);
+			}
+		}
+		FlickerPattern::OnBeat { depth } => {
+			if beat {
+				lighting_state.try_flash(FLASH_SECONDS, depth, &photosensitivity);
+			}
+		}
+	}
+
+	// A flash dips the lights: the room goes darker than it already is.
+	let flash = if lighting_state.flash_remaining > 0. { lighting_state.flash_depth } else { 0. };
+	let darkness = lighting.darkness.clamp(0., 1.);
+	let darkness = darkness + (1. - darkness) * flash;
+
+	for mut transform in &mut overlays {
+		transform.scale = Vec3::new(lighting.glow_radius, lighting.glow_radius, 1.);
+	}
+	for (ring, mut fill) in &mut rings {
+		fill.color = Color::BLACK.with_alpha(darkness * ring.0);
+	}
+}
+
+// -----------------------------------------------------------------------------
+// Power cycle lighting: on later cycles, the house loses power too
+// -----------------------------------------------------------------------------
+
+const POWER_CYCLE_LIGHTING: &str = "power_cycle";
+// From this FeverLevel on, a power cycle takes the room lights with it.
+const LIGHTS_OUT_FEVER_LEVEL: usize = 3;
+// How long the lights flicker once the power is back, before the room is lit again.
+const LIGHTS_SETTLE_SECONDS: f32 = 20.;
+
+// Seconds until the flickering after a power cycle settles (None while there's nothing to settle).
+#[derive(Resource, Debug, Default)]
+pub struct PowerCycleLighting {
+	pub settle_remaining: Option<f32>,
+}
+
+pub fn on_power_out_lights_out(
+	_event: On<PowerOut>,
+	fever_level: Res<FeverLevel>,
+	mut requests: ResMut<LightingRequests>,
+	mut power_cycle_lighting: ResMut<PowerCycleLighting>,
+) {
+	if fever_level.0 < LIGHTS_OUT_FEVER_LEVEL {
+		return;
+	}
+	// Dark until the power comes back, however long that takes.
+	power_cycle_lighting.settle_remaining = None;
+	requests.request(POWER_CYCLE_LIGHTING, LightingRequest {
+		darkness: 0.95,
+		glow_radius: 420.,
+		..LightingRequest::default()
+	});
+}
+
+// The phone comes back on battery; for a while the room stays dim and the lights can't quite decide.
+pub fn on_power_restored_lights_flicker(
+	_event: On<PowerRestored>,
+	fever_level: Res<FeverLevel>,
+	mut requests: ResMut<LightingRequests>,
+	mut power_cycle_lighting: ResMut<PowerCycleLighting>,
+) {
+	if fever_level.0 < LIGHTS_OUT_FEVER_LEVEL {
+		requests.release(POWER_CYCLE_LIGHTING);
+		return;
+	}
+	requests.request(POWER_CYCLE_LIGHTING, LightingRequest {
+		darkness: 0.55,
+		glow_radius: 600.,
+		flicker: FlickerPattern::Random { flashes_per_second: 1.2, depth: 0.8 },
+		priority: 1,
+	});
+	power_cycle_lighting.settle_remaining = Some(LIGHTS_SETTLE_SECONDS);
+}
+
+pub fn settle_power_cycle_lighting(
+	time: Res<Time>,
+	mut requests: ResMut<LightingRequests>,
+	mut power_cycle_lighting: ResMut<PowerCycleLighting>,
+) {
+	let Some(remaining) = power_cycle_lighting.settle_remaining.as_mut() else {
+		return;
+	};
+	*remaining -= time.delta_secs();
+	if *remaining <= 0. {
+		power_cycle_lighting.settle_remaining = None;
+		requests.release(POWER_CYCLE_LIGHTING);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn combined_takes_the_darkest_smallest_and_highest_priority_flicker() {
+		let mut requests = LightingRequests::default();
+		assert_eq!(requests.combined(), LightingRequest::default());
+
+		let rhythmic = FlickerPattern::Rhythmic { hz: 2., duty: 0.3, depth: 0.6 };
+		let random = FlickerPattern::Random { flashes_per_second: 1., depth: 0.5 };
+		requests.request("dim", LightingRequest { darkness: 0.4, glow_radius: 500., flicker: rhythmic, priority: 1 });
+		requests.request("dark", LightingRequest { darkness: 0.9, glow_radius: 700., flicker: random, priority: 2 });
+		// A steady request never takes the flicker, however high its priority.
+		requests.request("steady", LightingRequest { darkness: 0.1, glow_radius: 300., flicker: FlickerPattern::Steady, priority: 5 });
+
+		let combined = requests.combined();
+		assert_eq!(combined.darkness, 0.9);
+		assert_eq!(combined.glow_radius, 300.);
+		assert_eq!(combined.flicker, random);
+
+		requests.release("dark");
+		assert_eq!(requests.combined().flicker, rhythmic);
+	}
+
+	#[test]
+	fn try_flash_is_capped_by_photosensitivity() {
+		let photosensitivity = Photosensitivity { max_flashes_per_second: 2., max_flash_depth: 0.5 };
+		let mut state = LightingState { since_last_flash: 1., ..LightingState::default() };
+
+		// The depth is capped, and so is the length: at most half the gap between flashes.
+		state.try_flash(1., 0.9, &photosensitivity);
+		assert_eq!(state.flash_depth, 0.5);
+		assert_eq!(state.flash_remaining, 0.25);
+		assert_eq!(state.since_last_flash, 0.);
+
+		// Too soon after the last one: nothing happens.
+		state.flash_remaining = 0.;
+		state.since_last_flash = 0.4;
+		state.try_flash(0.1, 0.3, &photosensitivity);
+		assert_eq!(state.flash_depth, 0.5);
+		assert_eq!(state.since_last_flash, 0.4);
+
+		state.since_last_flash = 0.5;
+		state.try_flash(0.1, 0.3, &photosensitivity);
+		assert_eq!(state.flash_depth, 0.3);
+		assert_eq!(state.flash_remaining, 0.1);
+	}
+}
@@ -5,6 +5,9 @@
 // use std::fmt;
 use bevy::{
 	prelude::*,
+	app::HierarchyPropagatePlugin,
+	camera::visibility::RenderLayers,
+	sprite_render::Material2dPlugin,
 	window::WindowResolution,
 };
 
@@ -16,18 +19,63 @@ use bevy_vector_shapes::Shape2dPlugin;
 // =============================================================================
 
 mod window_utils;
+mod audio;
+mod music;
+mod beat_clock;
+mod key_sounds;
+mod voice_lines;
+mod ambient;
+mod settings;
+mod volume_screen;
 mod cleanup;
 mod component_utils;
 mod app_state;
 mod sent_message;
 mod color_utils;
+mod physics;
+mod keyboard;
+mod noise_utils;
+mod key_disorder;
+mod draft;
+mod draft_corruption;
+mod ghost_slide;
+mod power_cycle;
+mod blink;
+mod screen_cracks;
+mod mini_player;
+mod lighting;
+mod hurt;
+mod bubble_disorder;
+mod double_vision;
 
 use window_utils::*;
+use audio::*;
+use music::*;
+use beat_clock::*;
+use key_sounds::*;
+use voice_lines::*;
+use ambient::*;
+use settings::*;
+use volume_screen::*;
 use cleanup::*;
 use component_utils::*;
 use app_state::*;
 use sent_message::*;
 use color_utils::*;
+use physics::*;
+use keyboard::*;
+use key_disorder::*;
+use draft::*;
+use draft_corruption::*;
+use ghost_slide::*;
+use power_cycle::*;
+use blink::*;
+use screen_cracks::*;
+use mini_player::*;
+use lighting::*;
+use hurt::*;
+use bubble_disorder::*;
+use double_vision::*;
 
 // =============================================================================
 // Color constants and structs - moved to color_utils.rs.
@@ -60,7 +108,10 @@ const DEFAULT_KEY_SIZE: Vec2 = Vec2::new(90., DEFAULT_KEY_HEIGHT);
 const DEFAULT_SPECIAL_KEY_SIZE: Vec2 = Vec2::new(122., DEFAULT_KEY_HEIGHT);
 const DEFAULT_MINI_SPECIAL_KEY_SIZE: Vec2 = Vec2::new(116.5, DEFAULT_KEY_HEIGHT);
 const DEFAULT_SPACEBAR_SIZE: Vec2 = Vec2::new(519.5, DEFAULT_KEY_HEIGHT);
+// Return fills out row 4: DEFAULT_MINI_SPECIAL_KEY_SIZE.x + DEFAULT_KEY_SIZE.x + DEFAULT_ROW_3_INNER_MARGIN.
+const DEFAULT_RETURN_KEY_SIZE: Vec2 = Vec2::new(243.5, DEFAULT_KEY_HEIGHT);
 const DEFAULT_KEY_SPACING: f32 = 16.;
+const DEFAULT_KEY_CORNER_RADIUS: f32 = 10.;
 
 const DEFAULT_ROW_1_MARGIN: f32 = 18.;
 const DEFAULT_ROW_2_MARGIN: f32 = 71.;
@@ -68,6 +119,14 @@ const DEFAULT_ROW_3_INNER_MARGIN: f32 = 37.;
 const DEFAULT_ROW_3_OUTER_MARGIN: f32 = DEFAULT_ROW_1_MARGIN;
 const DEFAULT_ROW_4_MARGIN: f32 = DEFAULT_ROW_1_MARGIN;
 
+// Vertical layout (rows are stacked up from the bottom edge of the screen).
+const DEFAULT_KEY_ROW_SPACING: f32 = 30.;
+const DEFAULT_KEYBOARD_BOTTOM_MARGIN: f32 = 170.;	// Room for the ⨁ / 🎙 row.
+const DEFAULT_KEYBOARD_TOP_MARGIN: f32 = 130.;		// Room for the suggestions strip.
+const DEFAULT_KEYBOARD_HEIGHT: f32 = DEFAULT_KEYBOARD_BOTTOM_MARGIN
+	+ 4. * DEFAULT_KEY_HEIGHT + 3. * DEFAULT_KEY_ROW_SPACING
+	+ DEFAULT_KEYBOARD_TOP_MARGIN;
+
 const WINDOW_SCALE: (bool, f32) = (true, 0.5);
 
 // =============================================================================
@@ -97,6 +156,11 @@ fn main() {
 	}))
 
 	.add_plugins(Shape2dPlugin::default())
+	// Lets a whole keycap (shapes and all) be moved onto a render layer at once - see double_vision.rs.
+	.add_plugins(HierarchyPropagatePlugin::<RenderLayers>::new(PostUpdate))
+	.add_plugins(Material2dPlugin::<DoubleVisionMaterial>::default())
+	// Bevy audio by default, FMOD with the "fmod" feature (see audio/mod.rs).
+	.add_plugins(AudioManagerPlugin::default())
 
 	// +++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
 	// Initialize Resource values.
@@ -110,6 +174,23 @@ fn main() {
 	.init_resource::<WindowAwaitsCentering>()
 
 	.insert_resource(FeverLevel(0))
+	.init_resource::<FeverIntensity>()
+	.init_resource::<MusicBundles>()
+	.init_resource::<MusicLayers>()
+	.init_resource::<MusicSettings>()
+	.init_resource::<MusicDirector>()
+	.init_resource::<BeatClock>()
+	.init_resource::<KeySoundSettings>()
+	.init_resource::<KeySoundMorph>()
+	.init_resource::<VoiceLineSettings>()
+	.init_resource::<VoiceLineState>()
+	.init_resource::<AmbientSettings>()
+	.init_resource::<AmbientPlayback>()
+	.init_resource::<RadioSettings>()
+	.insert_resource(SavedSettings::load())
+	.init_resource::<VolumeScreen>()
+	.init_asset::<MusicBundle>()
+	.init_asset_loader::<MusicBundleLoader>()
 
 	.init_resource::<DarkModeEnabled>()
 	.init_resource::<AndroidModeEnabled>()
@@ -118,6 +199,37 @@ fn main() {
 
 	.init_resource::<NextIndex>()
 
+	.init_resource::<PhysicsBounds>()
+
+	.init_resource::<DetachedKeyBehavior>()
+	.init_resource::<KeyHitTestMode>()
+	.init_resource::<KeyDisorder>()
+
+	.init_resource::<GhostPrompt>()
+	.init_resource::<Draft>()
+	.init_resource::<TypedGlyphOffset>()
+	.init_resource::<GhostSlideSettings>()
+	.init_resource::<GhostSlideElapsed>()
+
+	.init_resource::<PowerCyclePolicy>()
+	.init_resource::<PowerCycleState>()
+	.init_resource::<BlinkSettings>()
+	.init_resource::<BlinkState>()
+	.init_resource::<DraftCorruptionSettings>()
+	.init_resource::<DraftMorph>()
+	.init_asset::<CannedLines>()
+	.init_asset_loader::<CannedLinesLoader>()
+	.init_resource::<ScreenCrackSettings>()
+	.init_resource::<ScreenCracks>()
+	.init_resource::<LightingRequests>()
+	.init_resource::<LightingState>()
+	.init_resource::<Photosensitivity>()
+	.init_resource::<PowerCycleLighting>()
+	.init_resource::<HurtSettings>()
+	.init_resource::<HurtState>()
+	.init_resource::<IllegibleScheme>()
+	.init_resource::<DoubleVisionSettings>()
+
 	.insert_resource(ClearColor(Color::BLACK)) // bevy built-in Resource, used for window clearing - might not use
 	;
 
@@ -127,28 +239,12 @@ fn main() {
 
 	app.init_state::<AppState>();
 
-	// app.add_systems(OnExit(AppState::Splash), (do_something, cleanup_system::<Cleanup<Splash>>).chain());
-	app.add_systems(OnExit(AppState::Splash), cleanup_system::<Cleanup<Splash>>);
-	// app.add_systems(OnTransition(AppState::Splash), some_transition_system);
-	// app.add_systems(OnTransition(AppState::Splash), some_transition_system);
-	// app.add_systems(OnEnter(AppState::Splash), enter_system::<Enter<Splash>>);
-
-	app.add_systems(OnExit(AppState::MainMenu), cleanup_system::<Cleanup<MainMenu>>);
-	// app.add_systems(OnTransition(AppState::MainMenu), some_transition_system);
-	// app.add_systems(OnEnter(AppState::MainMenu), enter_system::<Enter<MainMenu>>);
-
-	app.add_systems(OnExit(AppState::InGame), cleanup_system::<Cleanup<InGame>>);
-	// app.add_systems(OnTransition(AppState::InGame), some_transition_system);
+	// Cleanup<T> and LivesIn entities are despawned on leaving their states (for every AppState).
+	app.add_plugins(CleanupPlugin);
+	// app.add_systems(OnExit(AppState::Splash), do_something);
+	// app.add_systems(OnTransition { exited: AppState::MainMenu, entered: AppState::InGame }, some_transition_system);
 	// app.add_systems(OnEnter(AppState::InGame), enter_system::<Enter<InGame>>);
 
-	app.add_systems(OnExit(AppState::PauseMenu), cleanup_system::<Cleanup<PauseMenu>>);
-	// app.add_systems(OnTransition(AppState::PauseMenu), some_transition_system);
-	// app.add_systems(OnEnter(AppState::PauseMenu), enter_system::<Enter<PauseMenu>>);
-
-	app.add_systems(OnExit(AppState::Won), cleanup_system::<Cleanup<Won>>);
-	// app.add_systems(OnTransition(AppState::Won), some_transition_system);
-	// app.add_systems(OnEnter(AppState::Won), enter_system::<Enter<Won>>);
-
 	// To transition to a new state, pass the NextState resource (as mutable):
 	// mut next_state: ResMut<NextState<AppState>>,
 	// and in the system body, call e.g:
@@ -164,8 +260,8 @@ fn main() {
 	// PreStartup, Startup, PostStartup: these run once, on app launch.
 	// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
 
-	app.add_systems(PreStartup, (register_quit_cleanup_system, pre_startup).chain());
-	app.add_systems(Startup, startup);
+	app.add_systems(PreStartup, (register_quit_cleanup_system, register_blink_reactions, pre_startup).chain());
+	app.add_systems(Startup, (startup, spawn_eyelids, spawn_lighting_overlay, spawn_hurt_tint, load_canned_lines, load_music_bundles, spawn_distorted_layers, spawn_radio, apply_saved_settings));
 	app.add_systems(PostStartup, (init_window_resolution_scale_factor, post_startup).chain());
 
 	// .........................................................................
@@ -183,7 +279,7 @@ fn main() {
 	// First
 	// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
 
-	app.add_systems(First, first);
+	app.add_systems(First, (first, unshake_keyboard));
 
 	// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
 	// PreUpdate
@@ -204,7 +300,10 @@ fn main() {
 	app.add_systems(FixedFirst, fixed_first)
 	.add_systems(FixedPreUpdate, fixed_pre_update)
 	.add_systems(FixedUpdate, (
-		// resolve_velocity,
+		control_mini_players.run_if(volume_screen_closed),
+		integrate_velocity,
+		resolve_bounds_collisions,
+		land_mini_players,
 		advance_fever,
 		fixed_update,
 	).chain())
@@ -217,6 +316,73 @@ fn main() {
 
 	app.add_systems(Update, (
 		on_window_resized,
+		direct_music_on_state_change,
+		interpolate_physics_transforms,
+		(
+			type_from_physical_keyboard,
+			handle_key_pointer_press,
+			drag_held_keycaps,
+		).chain().run_if(power_on).run_if(volume_screen_closed),
+		(
+			toggle_volume_screen,
+			(volume_screen_input, sync_volume_screen).run_if(volume_screen_open),
+		).chain(),
+		advance_power_cycle,
+		(
+			read_blink_input.run_if(power_on).run_if(volume_screen_closed),
+			schedule_involuntary_blinks.run_if(power_on),
+			animate_blink,
+		).chain(),
+		(
+			update_fever_audio_parameter.run_if(resource_changed::<FeverLevel>),
+			ease_key_sound_morph,
+			update_voice_lines,
+			(
+				tune_radio.run_if(resource_changed::<FeverLevel>),
+				play_ambient_emitters,
+			).chain(),
+		),
+		(
+			ease_fever_intensity,
+			follow_fever_music.run_if(resource_changed::<FeverLevel>),
+			track_music_position,
+			run_music_transitions,
+			tick_beat_clock,
+			crossfade_music_layers.run_if(resource_changed::<FeverIntensity>.or(music_fading_in)),
+		).chain(),
+		spread_screen_cracks,
+		(settle_power_cycle_lighting, update_lighting).chain(),
+		fade_hurt_tint,
+		(
+			ease_double_vision_to_fever,
+			sync_double_vision_materials,
+		).chain(),
+		(
+			drift_bubble_colors,
+			stop_fallen_bubbles,
+			return_bubbles,
+			sync_bubble_colors,
+		).chain(),
+		(
+			squish_mini_players_on_big_input.run_if(power_on),
+			squash_mini_players,
+			fade_splats,
+		).chain(),
+		animate_key_disorder.run_if(key_disorder_active),
+		restore_keys_after_disorder.run_if(
+			resource_changed::<KeyDisorder>.and(not(resource_added::<KeyDisorder>))
+		),
+		(
+			rebuild_ghost_glyphs.run_if(resource_changed::<GhostPrompt>),
+			init_ghost_slides,
+			slide_ghost_glyphs,
+		).chain(),
+		(
+			advance_draft_morph,
+			apply_ghost_misalignment.run_if(resource_changed::<FeverLevel>),
+			rebuild_typed_glyphs.run_if(resource_changed::<Draft>),
+			offset_typed_glyphs.run_if(resource_changed::<TypedGlyphOffset>),
+		).chain(),
 		// update_finger
 	));
 	#[cfg(debug_assertions)]
@@ -241,19 +407,33 @@ fn main() {
 		on_android_mode_enabled_changed.run_if(
 			resource_changed::<AndroidModeEnabled>.and(not(resource_added::<AndroidModeEnabled>))
 		)));
+	app.add_systems(Update, apply_illegible_scheme
+		.after(on_dark_mode_enabled_changed)
+		.after(on_android_mode_enabled_changed)
+	);
 	
 	#[cfg(debug_assertions)]
 	app.add_systems(Update,
 		(
-			update_colors_on_color_scheme_change,
-			print_messages_on_color_scheme_change,
-		).chain().run_if(resource_changed::<ColorScheme>.and(not(resource_added::<ColorScheme>)))
+			(
+				update_colors_on_color_scheme_change,
+				update_key_colors_on_color_scheme_change,
+				update_draft_colors_on_color_scheme_change,
+			).chain().run_if(resource_changed::<ColorScheme>.and(not(resource_added::<ColorScheme>))),
+			// Only on the mode toggles: while the illegible scheme fades, ColorScheme changes every frame.
+			print_messages_on_color_scheme_change.run_if(
+				resource_changed::<DarkModeEnabled>.or(resource_changed::<AndroidModeEnabled>)
+					.and(not(resource_added::<DarkModeEnabled>))
+			),
+		).chain().after(apply_illegible_scheme)
 	);
 	#[cfg(not(debug_assertions))]
 	app.add_systems(Update,
-		update_colors_on_color_scheme_change.run_if(
-			resource_changed::<ColorScheme>.and(not(resource_added::<ColorScheme>))
-		)
+		(
+			update_colors_on_color_scheme_change,
+			update_key_colors_on_color_scheme_change,
+			update_draft_colors_on_color_scheme_change,
+		).run_if(resource_changed::<ColorScheme>.and(not(resource_added::<ColorScheme>)))
 	);
 
 	// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
@@ -263,6 +443,10 @@ fn main() {
 	app.add_systems(PostUpdate, (
 		post_update,
 		despawn_doomed_targets,
+		(
+			shake_keyboard,
+			follow_keycap_legends,
+		).chain().before(TransformSystems::Propagate),
 	));
 
 	// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
@@ -275,7 +459,43 @@ fn main() {
 	// Add observers.
 	// +++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
 
-	app.add_observer(on_key_pressed)
+	#[cfg(debug_assertions)]
+	app.add_observer(on_key_pressed);
+
+	app.add_observer(on_key_tap_sound)
+	.add_observer(on_pop_keycaps)
+	.add_observer(on_erupt_keycaps)
+	.add_observer(on_drift_legends)
+	.add_observer(on_drop_legends)
+	.add_observer(on_swap_legends)
+	.add_observer(on_restore_legends)
+	.add_observer(on_key_tap_edit_draft)
+	.add_observer(on_send_draft)
+	.add_observer(on_submit_draft)
+	.add_observer(on_corrupt_draft)
+	.add_observer(on_crack_screen)
+	.add_observer(on_repair_screen)
+	.add_observer(on_power_out_repair_screen)
+	.add_observer(on_shrink_player)
+	.add_observer(on_squish_mini_players)
+	.add_observer(on_flicker_beat)
+	.add_observer(on_music_beat_flicker)
+	.add_observer(on_power_out_lights_out)
+	.add_observer(on_power_restored_lights_flicker)
+	.add_observer(on_glyph_typed_check)
+	.add_observer(on_hurt)
+	.add_observer(on_hurt_say_line)
+	.add_observer(on_disorder_bubbles)
+	.add_observer(on_return_bubbles)
+	.add_observer(on_start_power_cycle)
+	.add_observer(on_power_out)
+	.add_observer(on_power_out_say_line)
+	.add_observer(on_say_line)
+	.add_observer(on_power_out_reset_ghost)
+	.add_observer(on_power_out_stop_draft_morph)
+	.add_observer(on_involuntary_blink)
+	.add_observer(on_music_transition)
+	.add_observer(on_stop_music)
 
 	.run();
 }
@@ -291,24 +511,20 @@ fn pre_startup() {
 
 fn startup(
 	mut commands: Commands,
-	asset_server: Res<AssetServer>,
 	mut next_index: ResMut<NextIndex>,
 	color_scheme: Res<ColorScheme>,
+	mut next_state: ResMut<NextState<AppState>>,
 ) {
+	// No splash screen or menu yet: straight into the game.
+	next_state.set(AppState::InGame);
+
 	commands.spawn((
-		Name::new("SplashCamera"),
-		Cleanup::<Splash>::new(),
+		Name::new("Camera"),
+		Cleanup::<Quit>::new(),
 		Camera2d::default(),
 		Msaa::Off,
 	));
 
-	commands.spawn((
-		Name::new("MusicPlayer"),
-		Cleanup::<MainMenu>::new(),	// TODO: change this is the music should persist through to the InGame app state.
-		AudioPlayer::new(asset_server.load("audio/music/chillopen.ogg")),
-		PlaybackSettings::LOOP,
-	));
-
 	// TODO: instead of passing in a transform, the message spawning function
 	// should handle placing a new message at a default bottom-edge alignment -
 	// so a position based on bubble height, in turn based on message length -
@@ -319,6 +535,8 @@ fn startup(
 	spawn_sent_message(&mut commands, &mut next_index, &color_scheme, "Ok, on it", true, true, None);
 	spawn_sent_message(&mut commands, &mut next_index, &color_scheme, "You got this! 😎", false, true, None);
 
+	spawn_keyboard(&mut commands, &color_scheme);
+
 	// TODO: adapt the below to draw a message bubble per sent_message
 	// commands.spawn(
     //     ShapeBundle::rect(
@@ -380,16 +598,7 @@ fn last() {}
 // Events, observers, and reactions
 // =============================================================================
 
-#[derive(Event, Debug)]
-struct KeyTap {
-	glyph: char
-}
-
-fn on_key_pressed(event: On<KeyTap>) {
-	println!("Key pressed: {}", event.glyph);
-	// TODO Play sound
-	// TODO type character in field
-}
+// KeyTap and on_key_pressed - moved to keyboard.rs.
 
 // =============================================================================
 // General use components and related functions - moved to component_utils.rs
@@ -431,12 +640,33 @@ fn advance_fever(mut fever_level: ResMut<FeverLevel>) {
 	}
 }
 
+// A smoothed, continuous take on the FeverLevel, from 0 (well) to 1 (as feverish as it gets),
+// for whatever should fade rather than switch (e.g. the music layers).
+#[derive(Resource, Default)]
+struct FeverIntensity(f32);
+const FEVER_INTENSITY_PER_LEVEL: f32 = 0.25;
+const FEVER_INTENSITY_EASE_PER_SECOND: f32 = 0.05;
+fn ease_fever_intensity(time: Res<Time>, fever_level: Res<FeverLevel>, mut fever_intensity: ResMut<FeverIntensity>) {
+	let target = (fever_level.0 as f32 * FEVER_INTENSITY_PER_LEVEL).min(1.);
+	let max_step = FEVER_INTENSITY_EASE_PER_SECOND * time.delta_secs();
+	let step = (target - fever_intensity.0).clamp(-max_step, max_step);
+	// Left untouched once it arrives, so resource_changed only fires while it moves.
+	if step != 0. {
+		fever_intensity.0 += step;
+	}
+}
+
+// FMOD's music (and anything else listening) gets the FeverLevel as the "fever" parameter.
+fn update_fever_audio_parameter(fever_level: Res<FeverLevel>, mut audio: ResMut<AudioManager>) {
+	audio.set_parameter(FEVER_AUDIO_PARAMETER, fever_level.0 as f32);
+}
+const FEVER_AUDIO_PARAMETER: &str = "fever";
+
 // =============================================================================
 // Miscellaneous Stubs
 // =============================================================================
 
-// Update transforms based on linear [and angular] velocity of entities such as roaming keys, falling teeth, sliding/melting letters. 
-fn _resolve_velocity() {}
+// Velocity integration for roaming keys, falling teeth, sliding/melting letters - moved to physics.rs.
 
 // Move the finger/hand shadow/silhouette/sprite to track the cursor (or move elsewise when it doesn't).
 fn _update_finger() {}
@@ -460,6 +690,17 @@ fn sandbox_clear_sent_messages(mut commands: Commands, msgs: Query<(Entity, &Msg
 fn sandbox_update(
 	mut dark_mode_enabled: ResMut<DarkModeEnabled>,
 	mut android_mode_enabled: ResMut<AndroidModeEnabled>,
+	mut key_disorder: ResMut<KeyDisorder>,
+	mut key_hit_test_mode: ResMut<KeyHitTestMode>,
+	mut detached_key_behavior: ResMut<DetachedKeyBehavior>,
+	mut fever_level: ResMut<FeverLevel>,
+	mut lighting_requests: ResMut<LightingRequests>,
+	mut sandbox_lighting: Local<usize>,
+	mut illegible_scheme: ResMut<IllegibleScheme>,
+	mut double_vision_settings: ResMut<DoubleVisionSettings>,
+	mut beat_clock: ResMut<BeatClock>,
+	mut voice_line_settings: ResMut<VoiceLineSettings>,
+	mut saved_settings: ResMut<SavedSettings>,
 	// msgs: Query<(Entity, &Text, &FontColor, &BkgColor, &Side)>,
 	_msgs: Query<(Entity, &MsgText, &FontColor, &BkgColor, &IsMine, &Side, &Index)>,
 	mut commands: Commands,
@@ -478,18 +719,150 @@ fn sandbox_update(
 	// 	// }
 	// }
 
-	if keyboard_input.just_pressed(KeyCode::KeyD) {
-		dark_mode_enabled.0 = !dark_mode_enabled.0;
-		println!("\nDEBUG: dark mode toggle ({})", dark_mode_enabled.0);
+	// Debug toggles live on the function keys, since letters now type.
+	if keyboard_input.just_pressed(KeyCode::F1) {
+		if keyboard_input.pressed(KeyCode::ShiftLeft) {
+			illegible_scheme.forced = !illegible_scheme.forced;
+			println!("\nDEBUG: illegible color scheme toggle ({})", illegible_scheme.forced);
+		} else {
+			dark_mode_enabled.0 = !dark_mode_enabled.0;
+			println!("\nDEBUG: dark mode toggle ({})", dark_mode_enabled.0);
+		}
 	}
 
-	if keyboard_input.just_pressed(KeyCode::KeyA) {
+	if keyboard_input.just_pressed(KeyCode::F2) {
 		android_mode_enabled.0 = !android_mode_enabled.0;
 		println!("\nDEBUG: Android mode toggle ({})", android_mode_enabled.0);
 	}
 
-	if keyboard_input.just_pressed(KeyCode::KeyF) {
-		commands.trigger(KeyTap { glyph: 'f' });
+	if keyboard_input.just_pressed(KeyCode::F3) {
+		if keyboard_input.pressed(KeyCode::ShiftLeft) {
+			*detached_key_behavior = match *detached_key_behavior {
+				DetachedKeyBehavior::Nothing => DetachedKeyBehavior::WrongGlyph,
+				DetachedKeyBehavior::WrongGlyph => DetachedKeyBehavior::Nothing,
+			};
+			println!("\nDEBUG: detached key behavior ({:?})", *detached_key_behavior);
+		} else {
+			commands.trigger(PopKeycaps(1));
+		}
+	}
+
+	if keyboard_input.just_pressed(KeyCode::F4) {
+		commands.trigger(EruptKeycaps);
+	}
+
+	if keyboard_input.just_pressed(KeyCode::F5) {
+		let disordered = key_disorder.strength > 0.;
+		key_disorder.strength = if disordered { 0. } else { 1. };
+		*key_hit_test_mode = if disordered { KeyHitTestMode::Topmost } else { KeyHitTestMode::Bottommost };
+		println!("\nDEBUG: key disorder toggle ({})", !disordered);
+	}
+
+	if keyboard_input.just_pressed(KeyCode::F6) {
+		if keyboard_input.pressed(KeyCode::ShiftLeft) {
+			for motion in [BubbleMotion::Fall, BubbleMotion::Slide, BubbleMotion::Spin, BubbleMotion::ColorDrift] {
+				commands.trigger(DisorderBubbles { count: 1, motion });
+			}
+		} else {
+			commands.trigger(SwapLegends(3));
+			commands.trigger(DriftLegends(2));
+			commands.trigger(DropLegends(1));
+		}
+	}
+
+	if keyboard_input.just_pressed(KeyCode::F7) {
+		if keyboard_input.pressed(KeyCode::ShiftLeft) {
+			commands.trigger(ReturnBubbles);
+		} else {
+			commands.trigger(RestoreLegends);
+		}
+	}
+
+	if keyboard_input.just_pressed(KeyCode::F8) {
+		fever_level.0 += 1;
+		println!("\nDEBUG: fever level up ({})", fever_level.0);
+	}
+
+	if keyboard_input.just_pressed(KeyCode::F9) {
+		commands.trigger(StartPowerCycle);
+	}
+
+	if keyboard_input.just_pressed(KeyCode::F10) {
+		if keyboard_input.pressed(KeyCode::ShiftLeft) {
+			commands.trigger(ShrinkPlayer(Vec2::ZERO));
+		} else {
+			commands.trigger(InvoluntaryBlink);
+		}
+	}
+
+	if keyboard_input.just_pressed(KeyCode::F11) {
+		if keyboard_input.pressed(KeyCode::ShiftLeft) {
+			// Cycle through the flicker patterns (then back to normal lighting).
+			*sandbox_lighting = (*sandbox_lighting + 1) % 5;
+			let flicker = match *sandbox_lighting {
+				1 => Some(FlickerPattern::Steady),
+				2 => Some(FlickerPattern::Random { flashes_per_second: 2., depth: 0.8 }),
+				3 => Some(FlickerPattern::Rhythmic { hz: 2., duty: 0.3, depth: 0.6 }),
+				4 => Some(FlickerPattern::OnBeat { depth: 0.7 }),
+				_ => None,
+			};
+			match flicker {
+				Some(flicker) => lighting_requests.request("sandbox", LightingRequest { darkness: 0.7, glow_radius: 500., flicker, priority: 10 }),
+				None => lighting_requests.release("sandbox"),
+			}
+			println!("\nDEBUG: sandbox lighting ({:?})", flicker);
+		} else {
+			commands.trigger(CorruptDraft { generator: None, send_when_done: false });
+		}
+	}
+
+	if keyboard_input.just_pressed(KeyCode::Digit0) {
+		if keyboard_input.pressed(KeyCode::ShiftLeft) {
+			// Takes the beat clock off the music and steps it by hand, one beat per press.
+			beat_clock.manual = true;
+			let beat_seconds = beat_clock.beat_seconds();
+			beat_clock.advance(beat_seconds);
+			println!("\nDEBUG: manual beat clock (beat {}, bar {})", beat_clock.beat(), beat_clock.bar());
+		} else {
+			commands.trigger(FlickerBeat);
+		}
+	}
+
+	if keyboard_input.just_pressed(KeyCode::Digit7) {
+		if keyboard_input.pressed(KeyCode::ShiftLeft) {
+			voice_line_settings.allow_profanity = !voice_line_settings.allow_profanity;
+			store_voice_line_settings(&mut saved_settings, &voice_line_settings);
+			saved_settings.save();
+			println!("\nDEBUG: profanity allowed? {}", voice_line_settings.allow_profanity);
+		} else {
+			commands.trigger(SayLine { tag: VoiceTag::Despair });
+		}
+	}
+
+	if keyboard_input.just_pressed(KeyCode::Digit8) {
+		// Back to the start of the music: on the next bar, or right away with shift.
+		commands.trigger(MusicTransition {
+			bundle: None,
+			cue: Some(String::from("start")),
+			quantize: if keyboard_input.pressed(KeyCode::ShiftLeft) { Quantize::Immediate } else { Quantize::Bar },
+			stinger: Some(String::from(HURT_SOUND_PATH)),
+		});
+	}
+
+	if keyboard_input.just_pressed(KeyCode::Digit9) {
+		double_vision_settings.forced = match double_vision_settings.forced {
+			Some(_) => None,
+			None => Some(1.),
+		};
+		println!("\nDEBUG: double vision toggle ({:?})", double_vision_settings.forced);
+	}
+
+	if keyboard_input.just_pressed(KeyCode::F12) {
+		if keyboard_input.pressed(KeyCode::ShiftLeft) {
+			commands.trigger(RepairScreen);
+		} else {
+			commands.trigger(CrackScreen(None));
+		}
 	}
 }
 
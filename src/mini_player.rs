@@ -0,0 +1,283 @@
+use bevy::prelude::{
+	Component, Event, On,
+	Entity, Name,
+	Query, Res, With, Without,
+	Commands,
+	ButtonInput, KeyCode, MouseButton,
+	Children,
+	Time, Transform, Visibility,
+	Color, Vec2, Vec3, Vec4,
+};
+use bevy::color::Alpha;
+use bevy_vector_shapes::prelude::*;
+use rand::Rng;
+
+use crate::cleanup::{Cleanup, PowerCycle};
+use crate::keyboard::{KeyTap, KeyKind, Keycap, KeyHitArea, Detached};
+use crate::physics::*;
+
+// =============================================================================
+// Mini player: the player, shrunk down to a tiny platformer character on the keyboard
+// =============================================================================
+
+// Arrow keys walk, Up jumps (none of them type). Landing hard enough on a keycap stomps it,
+// which taps the key through the same KeyTap path as everything else. Once the big player comes
+// back and types or taps, every mini player gets squished flat and leaves a splat behind.
+// There can be several at once; they all follow the same controls.
+
+// Trigger to shrink the player into (another) mini player, dropped in at this point.
+#[derive(Event, Debug)]
+pub struct ShrinkPlayer(pub Vec2);
+
+// Trigger to squish every mini player (the big player's finger coming down on them).
+#[derive(Event, Debug)]
+pub struct SquishMiniPlayers;
+
+const MINI_PLAYER_HALF_SIZE: Vec2 = Vec2::new(16., 26.);
+const MINI_PLAYER_Z: f32 = 7.;		// Over the keyboard, the ghost text and the typed glyphs (see double_vision.rs).
+const MINI_PLAYER_COLOR: Color = Color::srgb(0.98, 0.62, 0.22);
+const MINI_PLAYER_EYE_COLOR: Color = Color::srgb(0.1, 0.1, 0.12);
+const MINI_PLAYER_WALK_SPEED: f32 = 320.;
+const MINI_PLAYER_JUMP_SPEED: f32 = 1150.;
+// Landing faster than this (falling, world units per second) counts as a stomp.
+const STOMP_SPEED: f32 = 500.;
+const SQUISH_SECONDS: f32 = 0.12;
+const SPLAT_SECONDS: f32 = 1.6;
+const SPLAT_COLOR: Color = Color::srgb(0.86, 0.2, 0.24);
+
+#[derive(Component, Debug, Default)]
+pub struct MiniPlayer {
+	pub grounded: bool,
+}
+
+// The mini player is being flattened; it turns into a splat once that's done.
+#[derive(Component, Debug, Default)]
+pub struct Squished {
+	pub elapsed: f32,
+	pub feet: f32,		// Stays put while the rest of the body is pressed down onto it.
+}
+
+#[derive(Component, Debug, Default)]
+pub struct Splat(pub f32);
+
+pub fn on_shrink_player(
+	event: On<ShrinkPlayer>,
+	mut commands: Commands,
+) {
+	let transform = Transform::from_translation(event.0.extend(MINI_PLAYER_Z));
+	commands.spawn((
+		Name::new("MiniPlayer"),
+		Cleanup::<PowerCycle>::new(),
+		MiniPlayer::default(),
+		transform,
+		Visibility::default(),
+		PhysicsBodyBundle::from_transform(&transform, Vec2::ZERO, 0.),
+		Gravity::default(),
+		BoundsCollider { half_size: MINI_PLAYER_HALF_SIZE, restitution: 0. },
+		CollideWithScreen,
+	)).with_children(|parent| {
+		parent.spawn((
+			Name::new("MiniPlayerBody"),
+			ShapeBundle::rect(
+				&ShapeConfig {
+					color: MINI_PLAYER_COLOR,
+					corner_radii: Vec4::splat(10.),
+					..ShapeConfig::default_2d()
+				},
+				MINI_PLAYER_HALF_SIZE * 2.,
+			),
+		));
+		for side in [-1., 1.] {
+			parent.spawn((
+				Name::new("MiniPlayerEye"),
+				ShapeBundle::circle(
+					&ShapeConfig {
+						color: MINI_PLAYER_EYE_COLOR,
+						transform: Transform::from_xyz(side * 6., 10., 0.01),
+						..ShapeConfig::default_2d()
+					},
+					3.5,
+				),
+			));
+		}
+	});
+}
+
+// Runs in FixedUpdate, before integrate_velocity.
+pub fn control_mini_players(
+	keyboard_input: Res<ButtonInput<KeyCode>>,
+	mut mini_players: Query<(&MiniPlayer, &mut LinearVelocity), Without<Squished>>,
+) {
+	let mut walk = 0.;
+	if keyboard_input.pressed(KeyCode::ArrowLeft) {
+		walk -= 1.;
+	}
+	if keyboard_input.pressed(KeyCode::ArrowRight) {
+		walk += 1.;
+	}
+	// Checked as held, not just pressed: fixed steps can miss a one-frame press, and holding to hop is fine.
+	let jump = keyboard_input.pressed(KeyCode::ArrowUp);
+
+	for (mini_player, mut velocity) in &mut mini_players {
+		velocity.0.x = walk * MINI_PLAYER_WALK_SPEED;
+		if jump && mini_player.grounded {
+			velocity.0.y = MINI_PLAYER_JUMP_SPEED;
+		}
+	}
+}
+
+// Runs in FixedUpdate, after resolve_bounds_collisions: attached keycap tops are one-way platforms.
+pub fn land_mini_players(
+	mut commands: Commands,
+	bounds: Res<PhysicsBounds>,
+	mut mini_players: Query<(&mut MiniPlayer, &mut PhysicsPosition, &mut LinearVelocity), Without<Squished>>,
+	keycaps: Query<(&Keycap, &Transform, &KeyHitArea), Without<Detached>>,
+) {
+	for (mut mini_player, mut position, mut velocity) in &mut mini_players {
+		let feet = position.translation.y - MINI_PLAYER_HALF_SIZE.y;
+		let previous_feet = position.previous_translation().y - MINI_PLAYER_HALF_SIZE.y;
+		let on_floor = feet <= bounds.screen.min.y + 0.5;
+
+		let mut landed_on = None;
+		if velocity.0.y <= 0. {
+			for (keycap, transform, hit_area) in &keycaps {
+				let half_size = hit_area.0 * transform.scale.truncate().abs();
+				let top = transform.translation.y + half_size.y;
+				let overlaps_x = (position.translation.x - transform.translation.x).abs() <= half_size.x + MINI_PLAYER_HALF_SIZE.x * 0.5;
+				// Crossed the top this step (or is standing right on it).
+				if overlaps_x && previous_feet >= top - 0.5 && feet <= top + 0.5 {
+					landed_on = Some((keycap.kind, top));
+					break;
+				}
+			}
+		}
+
+		match landed_on {
+			Some((kind, top)) => {
+				let impact_speed = -velocity.0.y;
+				position.translation.y = top + MINI_PLAYER_HALF_SIZE.y;
+				velocity.0.y = 0.;
+				if !mini_player.grounded && impact_speed >= STOMP_SPEED {
+					stomp(&mut commands, kind);
+				}
+				mini_player.grounded = true;
+			}
+			None => {
+				mini_player.grounded = on_floor;
+			}
+		}
+	}
+}
+
+fn stomp(commands: &mut Commands, kind: KeyKind) {
+	if let Some(glyph) = kind.glyph() {
+		commands.trigger(KeyTap { glyph });
+	}
+}
+
+// The big player is back: any typing or tapping comes down on the mini players.
+pub fn squish_mini_players_on_big_input(
+	mut commands: Commands,
+	keyboard_input: Res<ButtonInput<KeyCode>>,
+	mouse_input: Res<ButtonInput<MouseButton>>,
+	mini_players: Query<(), (With<MiniPlayer>, Without<Squished>)>,
+) {
+	if mini_players.is_empty() {
+		return;
+	}
+	let typed = keyboard_input.get_just_pressed().any(|key_code| KeyKind::from_key_code(*key_code).is_some());
+	if typed || mouse_input.just_pressed(MouseButton::Left) {
+		commands.trigger(SquishMiniPlayers);
+	}
+}
+
+pub fn on_squish_mini_players(
+	_event: On<SquishMiniPlayers>,
+	mut commands: Commands,
+	mini_players: Query<(Entity, &PhysicsPosition), (With<MiniPlayer>, Without<Squished>)>,
+) {
+	for (entity, position) in &mini_players {
+		commands.entity(entity)
+			.remove::<(PhysicsBodyBundle, Gravity, BoundsCollider, CollideWithScreen)>()
+			.insert(Squished { elapsed: 0., feet: position.translation.y - MINI_PLAYER_HALF_SIZE.y });
+	}
+}
+
+pub fn squash_mini_players(
+	mut commands: Commands,
+	time: Res<Time>,
+	mut squished: Query<(Entity, &mut Squished, &mut Transform)>,
+) {
+	for (entity, mut squish, mut transform) in &mut squished {
+		squish.elapsed += time.delta_secs();
+		let t = (squish.elapsed / SQUISH_SECONDS).min(1.);
+		// Flattened against whatever it was standing on.
+		let height = 1. - 0.85 * t;
+		transform.scale = Vec3::new(1. + 0.9 * t, height, 1.);
+		transform.translation.y = squish.feet + MINI_PLAYER_HALF_SIZE.y * height;
+
+		if t >= 1. {
+			spawn_splat(&mut commands, Vec2::new(transform.translation.x, squish.feet));
+			commands.entity(entity).despawn();
+		}
+	}
+}
+
+fn spawn_splat(commands: &mut Commands, at: Vec2) {
+	let mut rng = rand::rng();
+	commands.spawn((
+		Name::new("Splat"),
+		Cleanup::<PowerCycle>::new(),
+		Splat::default(),
+		Transform::from_translation(at.extend(MINI_PLAYER_Z)),
+		Visibility::default(),
+	)).with_children(|parent| {
+		parent.spawn((
+			Name::new("SplatBlob"),
+			ShapeBundle::circle(
+				&ShapeConfig {
+					color: SPLAT_COLOR,
+					transform: Transform::from_scale(Vec3::new(1.6, 0.45, 1.)),
+					..ShapeConfig::default_2d()
+				},
+				24.,
+			),
+		));
+		for _ in 0..rng.random_range(5..9) {
+			let direction = Vec2::from_angle(rng.random_range(0. ..std::f32::consts::PI));
+			let distance = rng.random_range(30. ..70.);
+			parent.spawn((
+				Name::new("SplatDroplet"),
+				ShapeBundle::circle(
+					&ShapeConfig {
+						color: SPLAT_COLOR,
+						transform: Transform::from_translation((direction * Vec2::new(distance, distance * 0.4)).extend(0.)),
+						..ShapeConfig::default_2d()
+					},
+					rng.random_range(3. ..8.),
+				),
+			));
+		}
+	});
+}
+
+// Splats hang around for a moment, then fade away.
+pub fn fade_splats(
+	mut commands: Commands,
+	time: Res<Time>,
+	mut splats: Query<(Entity, &mut Splat, &Children)>,
+	mut fills: Query<&mut ShapeFill>,
+) {
+	for (entity, mut splat, children) in &mut splats {
+		splat.0 += time.delta_secs();
+		let fade = ((splat.0 / SPLAT_SECONDS - 0.5) * 2.).clamp(0., 1.);
+		for child in children.iter() {
+			if let Ok(mut fill) = fills.get_mut(*child) {
+				fill.color = SPLAT_COLOR.with_alpha(1. - fade);
+			}
+		}
+		if splat.0 >= SPLAT_SECONDS {
+			commands.entity(entity).despawn();
+		}
+	}
+}
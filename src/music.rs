@@ -0,0 +1,540 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{
+	Resource, Event, On,
+	Asset, Assets, AssetServer, Handle,
+	Res, ResMut, Local,
+	Commands, MessageReader, StateTransitionEvent,
+	Time,
+	TypePath,
+	warn,
+};
+use bevy::asset::{AssetLoader, LoadContext, io::Reader};
+
+use crate::{FeverLevel, FeverIntensity};
+use crate::app_state::AppState;
+use crate::audio::{AudioManager, AudioBus, Sound, SoundInstance};
+
+// =============================================================================
+// Music: bundles of layered stems, mixed by how feverish things are
+// =============================================================================
+
+// A music bundle is one piece of music split into stems that share a tempo and length
+// (see assets/music/*.music). All of a bundle's stems start together and stay lined up
+// (see AudioManager::play_synced); only their volumes change, following the FeverIntensity.
+// Anything bigger - another bundle, or a jump to one of the bundle's cue points - is a
+// MusicTransition, which waits for the next beat or bar of whatever is playing.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MusicStem {
+	pub path: String,
+	pub fade_in: (f32, f32),			// FeverIntensity range over which the stem comes in; (0, 0) is always on.
+	pub fade_out: Option<(f32, f32)>,	// ...and the range over which it goes out again, if it does.
+}
+impl MusicStem {
+	pub fn volume(&self, intensity: f32) -> f32 {
+		let fade_in = ramp(intensity, self.fade_in);
+		let fade_out = self.fade_out.map_or(0., |range| ramp(intensity, range));
+		fade_in * (1. - fade_out)
+	}
+}
+
+// 0 below the range, 1 above it, linear in between.
+fn ramp(value: f32, (from, to): (f32, f32)) -> f32 {
+	if to <= from {
+		return if value >= from { 1. } else { 0. };
+	}
+	((value - from) / (to - from)).clamp(0., 1.)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Quantize {
+	Immediate,
+	#[default]
+	Beat,
+	Bar,
+}
+
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct MusicBundle {
+	pub stems: Vec<MusicStem>,
+	pub bpm: f32,
+	pub beats_per_bar: u32,
+	pub cues: HashMap<String, f32>,		// Named spots (seconds in) to jump to.
+}
+impl MusicBundle {
+	pub fn beat_seconds(&self) -> f32 {
+		60. / self.bpm.max(1.)
+	}
+
+	pub fn bar_seconds(&self) -> f32 {
+		self.beat_seconds() * self.beats_per_bar.max(1) as f32
+	}
+
+	// The first beat (or bar) line at or after this position (seconds in).
+	pub fn next_boundary(&self, position: f32, quantize: Quantize) -> f32 {
+		let step = match quantize {
+			Quantize::Immediate => return position,
+			Quantize::Beat => self.beat_seconds(),
+			Quantize::Bar => self.bar_seconds(),
+		};
+		(position / step).ceil() * step
+	}
+}
+
+// -----------------------------------------------------------------------------
+// Music bundle asset: plain text, one setting per line (see assets/music/sick_day.music)
+// -----------------------------------------------------------------------------
+
+pub const MUSIC_BUNDLE_FILES: [(&str, &str); 1] = [
+	("sick_day", "music/sick_day.music"),
+];
+
+#[derive(TypePath, Default)]
+pub struct MusicBundleLoader;
+
+impl AssetLoader for MusicBundleLoader {
+	type Asset = MusicBundle;
+	type Settings = ();
+	type Error = std::io::Error;
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &(),
+		_load_context: &mut LoadContext<'_>,
+	) -> Result<MusicBundle, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		parse_music_bundle(&String::from_utf8_lossy(&bytes))
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["music"]
+	}
+}
+
+fn parse_music_bundle(text: &str) -> Result<MusicBundle, std::io::Error> {
+	let mut bundle = MusicBundle { stems: Vec::new(), bpm: 120., beats_per_bar: 4, cues: HashMap::new() };
+
+	for (index, line) in text.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let invalid = |what: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("line {}: {what}", index + 1));
+		let number = |word: Option<&str>| word.and_then(|word| word.parse::<f32>().ok()).ok_or_else(|| invalid("expected a number"));
+
+		let mut words = line.split_whitespace();
+		match words.next() {
+			Some("bpm") => bundle.bpm = number(words.next())?,
+			Some("beats_per_bar") => bundle.beats_per_bar = number(words.next())? as u32,
+			Some("cue") => {
+				let name = words.next().ok_or_else(|| invalid("cue needs a name"))?;
+				bundle.cues.insert(name.to_string(), number(words.next())?);
+			}
+			Some("stem") => {
+				let path = words.next().ok_or_else(|| invalid("stem needs a path"))?;
+				let mut stem = MusicStem { path: path.to_string(), fade_in: (0., 0.), fade_out: None };
+				while let Some(word) = words.next() {
+					let range = (number(words.next())?, number(words.next())?);
+					match word {
+						"in" => stem.fade_in = range,
+						"out" => stem.fade_out = Some(range),
+						_ => return Err(invalid("stem ranges start with \"in\" or \"out\"")),
+					}
+				}
+				bundle.stems.push(stem);
+			}
+			Some(other) => return Err(invalid(&format!("unknown setting {other:?}"))),
+			None => {}
+		}
+	}
+	Ok(bundle)
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct MusicBundles(pub HashMap<String, Handle<MusicBundle>>);
+
+pub fn load_music_bundles(mut bundles: ResMut<MusicBundles>, asset_server: Res<AssetServer>) {
+	for (name, path) in MUSIC_BUNDLE_FILES {
+		bundles.0.insert(name.to_string(), asset_server.load(path));
+	}
+}
+
+// -----------------------------------------------------------------------------
+// Playing and switching
+// -----------------------------------------------------------------------------
+
+const MUSIC_BUNDLE_FADE_SECONDS: f32 = 1.5;
+// Volume changes smaller than this aren't worth sending to the backend.
+const MUSIC_VOLUME_EPSILON: f32 = 0.005;
+
+// Trigger to switch bundle and/or jump to a cue point, on the next beat or bar of what's playing.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct MusicTransition {
+	pub bundle: Option<String>,		// None stays on the playing bundle.
+	pub cue: Option<String>,		// Where to start; None is the top (or, on the same bundle, carry on).
+	pub quantize: Quantize,
+	pub stinger: Option<String>,	// A sound (path) played on the switch.
+}
+
+#[derive(Debug)]
+struct PendingMusicTransition {
+	transition: MusicTransition,
+	at: Option<f32>,		// Position to switch at, once worked out.
+	from: f32,				// Position it was worked out at (so a loop back to the top isn't missed).
+}
+
+// The bundle that is playing.
+#[derive(Resource, Debug, Default)]
+pub struct MusicLayers {
+	pub bundle: Option<String>,
+	pub position: f32,						// Seconds into the bundle.
+	stems: Vec<(SoundInstance, f32)>,		// Each stem's sound, and the volume it was last set to.
+	fade_in: f32,							// How far (0 to 1) the bundle has come in since it started.
+	pending: Option<PendingMusicTransition>,
+}
+impl MusicLayers {
+	pub fn lead_stem(&self) -> Option<SoundInstance> {
+		self.stems.first().map(|(instance, _)| *instance)
+	}
+}
+
+// Run condition: a bundle has started and is still coming in (see crossfade_music_layers).
+pub fn music_fading_in(layers: Res<MusicLayers>) -> bool {
+	layers.bundle.is_some() && layers.fade_in < 1.
+}
+
+// Trigger to stop whatever is playing (and drop any transition still waiting).
+#[derive(Event, Debug)]
+pub struct StopMusic {
+	pub fade_seconds: f32,
+}
+
+// A later transition replaces one still waiting for its beat.
+pub fn on_music_transition(
+	event: On<MusicTransition>,
+	mut layers: ResMut<MusicLayers>,
+) {
+	layers.pending = Some(PendingMusicTransition { transition: event.event().clone(), at: None, from: 0. });
+}
+
+pub fn on_stop_music(
+	event: On<StopMusic>,
+	mut audio: ResMut<AudioManager>,
+	mut layers: ResMut<MusicLayers>,
+) {
+	for (instance, _) in layers.stems.drain(..) {
+		audio.stop(instance, event.fade_seconds);
+	}
+	layers.bundle = None;
+	layers.pending = None;
+	layers.position = 0.;
+}
+
+// Counts along with the music, taking the backend's word for it whenever it has one.
+pub fn track_music_position(
+	time: Res<Time>,
+	audio: Res<AudioManager>,
+	mut layers: ResMut<MusicLayers>,
+) {
+	if layers.bundle.is_none() {
+		return;
+	}
+	let reported = layers.lead_stem().and_then(|lead| audio.position(lead));
+	layers.position = reported.unwrap_or(layers.position + time.delta_secs());
+}
+
+pub fn run_music_transitions(
+	mut audio: ResMut<AudioManager>,
+	bundles: Res<MusicBundles>,
+	bundle_assets: Res<Assets<MusicBundle>>,
+	mut layers: ResMut<MusicLayers>,
+) {
+	let layers = &mut *layers;
+	let Some(pending) = layers.pending.as_mut() else {
+		return;
+	};
+	let Some(name) = pending.transition.bundle.clone().or_else(|| layers.bundle.clone()) else {
+		warn!("Music transition with no bundle, and none playing");
+		layers.pending = None;
+		return;
+	};
+	let Some(handle) = bundles.0.get(&name) else {
+		warn!("No music bundle named {name:?}");
+		layers.pending = None;
+		return;
+	};
+	// Still loading: try again next frame.
+	let Some(bundle) = bundle_assets.get(handle) else {
+		return;
+	};
+
+	// Switch on the playing bundle's beat grid (straight away if nothing is playing).
+	let playing = layers.bundle.as_ref().and_then(|name| bundles.0.get(name)).and_then(|handle| bundle_assets.get(handle));
+	let at = *pending.at.get_or_insert_with(|| {
+		pending.from = layers.position;
+		match playing {
+			Some(playing) => playing.next_boundary(layers.position, pending.transition.quantize),
+			None => layers.position,
+		}
+	});
+	let looped = layers.position < pending.from;
+	if layers.position < at && !looped {
+		return;
+	}
+
+	let Some(PendingMusicTransition { transition, .. }) = layers.pending.take() else {
+		return;
+	};
+	if let Some(stinger) = &transition.stinger {
+		audio.play(Sound::new(stinger.clone(), AudioBus::Music));
+	}
+	let cue = transition.cue.as_ref().map(|cue| {
+		bundle.cues.get(cue).copied().unwrap_or_else(|| {
+			warn!("No cue named {cue:?} in music bundle {name:?}");
+			0.
+		})
+	});
+
+	if layers.bundle.as_ref() == Some(&name) {
+		// Same bundle: only a cue moves it, and that's a jump, straight there - there's nothing to fade between.
+		if let (Some(seconds), Some(lead)) = (cue, layers.lead_stem()) {
+			audio.seek(lead, seconds);
+			layers.position = seconds;
+		}
+		return;
+	}
+
+	for (instance, _) in layers.stems.drain(..) {
+		audio.stop(instance, MUSIC_BUNDLE_FADE_SECONDS);
+	}
+	// The new stems start silent and come in as the old ones go out (see crossfade_music_layers).
+	let start_at = cue.unwrap_or(0.);
+	let sounds = bundle.stems.iter()
+		.map(|stem| Sound::new(stem.path.clone(), AudioBus::Music).with_volume(0.).looping())
+		.collect();
+	let instances = audio.play_synced(sounds, start_at);
+	layers.stems = instances.into_iter().map(|instance| (instance, 0.)).collect();
+	layers.fade_in = 0.;
+	layers.bundle = Some(name);
+	layers.position = start_at;
+}
+
+// This runs when FeverIntensity changes, and while a new bundle fades in (see App setup).
+// FeverIntensity already eases, so volumes just follow it.
+pub fn crossfade_music_layers(
+	time: Res<Time>,
+	mut audio: ResMut<AudioManager>,
+	bundles: Res<MusicBundles>,
+	bundle_assets: Res<Assets<MusicBundle>>,
+	fever_intensity: Res<FeverIntensity>,
+	mut layers: ResMut<MusicLayers>,
+) {
+	let layers = &mut *layers;
+	let bundle = layers.bundle.as_ref()
+		.and_then(|name| bundles.0.get(name))
+		.and_then(|handle| bundle_assets.get(handle));
+	let Some(bundle) = bundle else {
+		return;
+	};
+	layers.fade_in = (layers.fade_in + time.delta_secs() / MUSIC_BUNDLE_FADE_SECONDS).min(1.);
+	let fade_in = layers.fade_in;
+	let targets: Vec<f32> = bundle.stems.iter().map(|stem| stem.volume(fever_intensity.0) * fade_in).collect();
+	for ((instance, volume), target) in layers.stems.iter_mut().zip(targets) {
+		// Always land exactly on silent and full, however small the last step.
+		let settled = (target == 0. || target == 1.) && target != *volume;
+		if (target - *volume).abs() > MUSIC_VOLUME_EPSILON || settled {
+			audio.set_volume(*instance, target);
+			*volume = target;
+		}
+	}
+}
+
+// -----------------------------------------------------------------------------
+// Fever stages: which bundle (and cue) each FeverLevel calls for
+// -----------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MusicStage {
+	pub bundle: &'static str,
+	pub cue: Option<&'static str>,		// Jumped to when the stage begins.
+	pub quantize: Quantize,
+	pub stinger: Option<&'static str>,
+}
+impl MusicStage {
+	const CALM: Self = Self {
+		bundle: "sick_day",
+		cue: None,
+		quantize: Quantize::Bar,
+		stinger: None,
+	};
+}
+
+// One entry per FeverLevel; levels past the end use the last entry.
+#[derive(Resource, Debug)]
+pub struct MusicSettings {
+	pub stages: Vec<MusicStage>,
+}
+impl Default for MusicSettings {
+	fn default() -> Self {
+		Self {
+			stages: vec![
+				MusicStage::CALM,
+				MusicStage::CALM,
+				MusicStage::CALM,
+				MusicStage::CALM,
+				// The abrupt shift: back to the top, as if nothing had happened.
+				MusicStage { cue: Some("start"), ..MusicStage::CALM },
+			],
+		}
+	}
+}
+impl MusicSettings {
+	pub fn stage(&self, fever_level: usize) -> MusicStage {
+		self.stages.get(fever_level)
+			.or(self.stages.last())
+			.copied()
+			.unwrap_or(MusicStage::CALM)
+	}
+}
+
+// This runs when FeverLevel changes (see App setup), including the very first time, which starts the music.
+pub fn follow_fever_music(
+	mut commands: Commands,
+	fever_level: Res<FeverLevel>,
+	settings: Res<MusicSettings>,
+	mut current: Local<Option<MusicStage>>,
+) {
+	let stage = settings.stage(fever_level.0);
+	if *current == Some(stage) {
+		return;
+	}
+	*current = Some(stage);
+	commands.trigger(MusicTransition {
+		bundle: Some(stage.bundle.to_string()),
+		cue: stage.cue.map(String::from),
+		quantize: stage.quantize,
+		stinger: stage.stinger.map(String::from),
+	});
+}
+
+// -----------------------------------------------------------------------------
+// App states: what the music does when the app moves from one to another
+// -----------------------------------------------------------------------------
+
+// The music isn't an entity, so no state's cleanup ever touches it; it only changes when told to.
+// On each AppState change the director finds the first handoff that matches and carries it out.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MusicHandoff {
+	Continue,					// Keep playing as if nothing happened.
+	Transition(MusicTransition),	// On the beat: crossfade to other music, or jump to a cue in this music.
+	Stop { fade_seconds: f32 },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MusicHandoffRule {
+	pub from: Option<AppState>,		// None matches any state (including none, at launch).
+	pub to: Option<AppState>,
+	pub handoff: MusicHandoff,
+}
+
+#[derive(Resource, Debug)]
+pub struct MusicDirector {
+	pub rules: Vec<MusicHandoffRule>,	// First match wins.
+	pub fallback: MusicHandoff,
+}
+impl Default for MusicDirector {
+	fn default() -> Self {
+		let rule = |from, to, handoff| MusicHandoffRule { from, to, handoff };
+		Self {
+			rules: vec![
+				// The menu music carries straight on into the game, and through pausing.
+				rule(Some(AppState::MainMenu), Some(AppState::InGame), MusicHandoff::Continue),
+				rule(Some(AppState::InGame), Some(AppState::PauseMenu), MusicHandoff::Continue),
+				rule(Some(AppState::PauseMenu), Some(AppState::InGame), MusicHandoff::Continue),
+				rule(None, Some(AppState::Won), MusicHandoff::Stop { fade_seconds: 3. }),
+				// Back to the menu from anywhere: back to the top.
+				rule(None, Some(AppState::MainMenu), MusicHandoff::Transition(MusicTransition {
+					bundle: Some(String::from("sick_day")),
+					cue: Some(String::from("start")),
+					quantize: Quantize::Bar,
+					stinger: None,
+				})),
+			],
+			fallback: MusicHandoff::Continue,
+		}
+	}
+}
+impl MusicDirector {
+	pub fn handoff(&self, from: Option<AppState>, to: Option<AppState>) -> &MusicHandoff {
+		self.rules.iter()
+			.find(|rule| rule.from.is_none_or(|state| Some(state) == from) && rule.to.is_none_or(|state| Some(state) == to))
+			.map_or(&self.fallback, |rule| &rule.handoff)
+	}
+}
+
+pub fn direct_music_on_state_change(
+	mut commands: Commands,
+	mut transitions: MessageReader<StateTransitionEvent<AppState>>,
+	director: Res<MusicDirector>,
+) {
+	for transition in transitions.read() {
+		if transition.exited == transition.entered {
+			continue;
+		}
+		match director.handoff(transition.exited, transition.entered) {
+			MusicHandoff::Continue => {}
+			MusicHandoff::Transition(music_transition) => commands.trigger(music_transition.clone()),
+			MusicHandoff::Stop { fade_seconds } => commands.trigger(StopMusic { fade_seconds: *fade_seconds }),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_music_bundle_reads_every_setting() {
+		let bundle = parse_music_bundle("
+			# A comment, then a blank line.
+
+			bpm 96
+			beats_per_bar 3
+			cue chorus 12.5
+			stem audio/music/drums.ogg
+			stem audio/music/pads.ogg in 0.2 0.5 out 0.8 1
+		").unwrap();
+
+		assert_eq!(bundle.bpm, 96.);
+		assert_eq!(bundle.beats_per_bar, 3);
+		assert_eq!(bundle.cues.get("chorus"), Some(&12.5));
+		assert_eq!(bundle.stems, vec![
+			MusicStem { path: "audio/music/drums.ogg".to_string(), fade_in: (0., 0.), fade_out: None },
+			MusicStem { path: "audio/music/pads.ogg".to_string(), fade_in: (0.2, 0.5), fade_out: Some((0.8, 1.)) },
+		]);
+	}
+
+	#[test]
+	fn parse_music_bundle_rejects_bad_lines() {
+		for text in [
+			"tempo 120",
+			"bpm fast",
+			"cue",
+			"cue chorus",
+			"stem",
+			"stem drums.ogg up 0 1",
+			"stem drums.ogg in 0",
+		] {
+			assert!(parse_music_bundle(text).is_err(), "{text:?} parsed");
+		}
+	}
+
+	#[test]
+	fn parse_music_bundle_says_which_line_is_wrong() {
+		let error = parse_music_bundle("bpm 120\n\nbeats_per_bar four").unwrap_err();
+		assert!(error.to_string().starts_with("line 3:"), "{error}");
+	}
+}
@@ -0,0 +1,18 @@
+use bevy::prelude::Vec2;
+
+// =============================================================================
+// Cheap smooth noise
+// =============================================================================
+
+// A smooth wobble in [-1, 1] built from a few sines with incommensurate frequencies,
+// so it doesn't visibly repeat. Not a real gradient noise, but plenty for wandering keys.
+// Different seeds give unrelated paths.
+pub fn smooth_noise(t: f32, seed: f32) -> f32 {
+	((t + seed * 12.9898).sin()
+		+ (t * 1.73 + seed * 78.233).sin() * 0.5
+		+ (t * 2.91 + seed * 37.719).sin() * 0.25) / 1.75
+}
+
+pub fn smooth_noise_2d(t: f32, seed: f32) -> Vec2 {
+	Vec2::new(smooth_noise(t, seed), smooth_noise(t, seed + 101.7))
+}
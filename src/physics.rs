@@ -0,0 +1,316 @@
+use bevy::prelude::{
+	Component, Bundle, Resource,
+	Query, Res, Has,
+	Time, Fixed,
+	Transform, Quat, EulerRot,
+	Vec2, Rect,
+};
+
+use crate::{VIRTUAL_RESOLUTION, DEFAULT_KEYBOARD_HEIGHT};
+
+// =============================================================================
+// Velocity integration
+// =============================================================================
+
+// Motion for roaming keys, falling teeth, sliding/melting letters and the like.
+// Bodies are simulated in FixedUpdate against their PhysicsPosition, and the rendered Transform
+// is interpolated between the last two fixed steps each frame, so motion stays smooth
+// when the frame rate and the fixed timestep disagree.
+
+pub const DEFAULT_GRAVITY: Vec2 = Vec2::new(0., -2400.);
+
+#[derive(Component, Debug, Default)]
+pub struct LinearVelocity(pub Vec2);		// World units per second.
+
+#[derive(Component, Debug, Default)]
+pub struct AngularVelocity(pub f32);		// Radians per second, counterclockwise.
+
+// Fraction of velocity shed per second (applied exponentially, so it's framerate-independent).
+#[derive(Component, Debug)]
+pub struct Damping {
+	pub linear: f32,
+	pub angular: f32,
+}
+impl Damping {
+	pub fn apply(&self, velocity: &mut LinearVelocity, angular_velocity: &mut AngularVelocity, dt: f32) {
+		velocity.0 *= (-self.linear * dt).exp();
+		angular_velocity.0 *= (-self.angular * dt).exp();
+	}
+}
+
+#[derive(Component, Debug)]
+pub struct Gravity(pub Vec2);
+impl Default for Gravity {
+	fn default() -> Self {
+		Self(DEFAULT_GRAVITY)
+	}
+}
+
+// The simulated pose, as of the latest fixed step and the one before it.
+// Only the xy plane is simulated; the Transform's z (draw order) is left alone.
+#[derive(Component, Debug, Default)]
+pub struct PhysicsPosition {
+	pub translation: Vec2,
+	pub rotation: f32,
+	previous_translation: Vec2,
+	previous_rotation: f32,
+}
+impl PhysicsPosition {
+	pub fn from_transform(transform: &Transform) -> Self {
+		let translation = transform.translation.truncate();
+		let rotation = transform.rotation.to_euler(EulerRot::XYZ).2;
+		Self {
+			translation,
+			rotation,
+			previous_translation: translation,
+			previous_rotation: rotation,
+		}
+	}
+
+	// Where the body was as of the fixed step before the latest one.
+	pub fn previous_translation(&self) -> Vec2 {
+		self.previous_translation
+	}
+}
+
+// Add this along with a Transform to make an entity move. Damping, Gravity and
+// BoundsCollider (plus the CollideWith* markers) are opt-in and can be inserted alongside.
+#[derive(Bundle, Debug, Default)]
+pub struct PhysicsBodyBundle {
+	pub velocity: LinearVelocity,
+	pub angular_velocity: AngularVelocity,
+	pub position: PhysicsPosition,
+}
+impl PhysicsBodyBundle {
+	pub fn from_transform(transform: &Transform, velocity: Vec2, angular_velocity: f32) -> Self {
+		Self {
+			velocity: LinearVelocity(velocity),
+			angular_velocity: AngularVelocity(angular_velocity),
+			position: PhysicsPosition::from_transform(transform),
+		}
+	}
+}
+
+// Runs in FixedUpdate.
+pub fn integrate_velocity(
+	time: Res<Time>,
+	mut bodies: Query<(
+		&mut PhysicsPosition,
+		&mut LinearVelocity,
+		&mut AngularVelocity,
+		Option<&Damping>,
+		Option<&Gravity>,
+	)>,
+) {
+	// In FixedUpdate, Res<Time> is the fixed clock, so this is the fixed timestep.
+	let dt = time.delta_secs();
+
+	for (mut position, mut velocity, mut angular_velocity, damping, gravity) in &mut bodies {
+		position.previous_translation = position.translation;
+		position.previous_rotation = position.rotation;
+
+		if let Some(gravity) = gravity {
+			velocity.0 += gravity.0 * dt;
+		}
+		if let Some(damping) = damping {
+			damping.apply(&mut velocity, &mut angular_velocity, dt);
+		}
+
+		position.translation += velocity.0 * dt;
+		position.rotation += angular_velocity.0 * dt;
+	}
+}
+
+// Runs in Update: place the rendered Transform between the last two simulated poses.
+pub fn interpolate_physics_transforms(
+	fixed_time: Res<Time<Fixed>>,
+	mut bodies: Query<(&mut Transform, &PhysicsPosition)>,
+) {
+	let alpha = fixed_time.overstep_fraction().clamp(0., 1.);
+
+	for (mut transform, position) in &mut bodies {
+		let translation = position.previous_translation.lerp(position.translation, alpha);
+		let rotation = position.previous_rotation + (position.rotation - position.previous_rotation) * alpha;
+		transform.translation.x = translation.x;
+		transform.translation.y = translation.y;
+		transform.rotation = Quat::from_rotation_z(rotation);
+	}
+}
+
+// =============================================================================
+// Bounds collisions
+// =============================================================================
+
+// Regions that colliding bodies bounce off of, in world coordinates (origin at screen center).
+#[derive(Resource, Debug)]
+pub struct PhysicsBounds {
+	pub screen: Rect,			// Bodies are kept inside this.
+	pub keyboard: Rect,			// Bodies are kept outside this.
+}
+impl Default for PhysicsBounds {
+	fn default() -> Self {
+		let res = VIRTUAL_RESOLUTION.as_vec2();
+		let screen = Rect::from_center_size(Vec2::ZERO, res);
+		let keyboard = Rect::new(screen.min.x, screen.min.y, screen.max.x, screen.min.y + DEFAULT_KEYBOARD_HEIGHT);
+		Self { screen, keyboard }
+	}
+}
+
+// Axis-aligned box used for bounds collisions (rotation is ignored).
+#[derive(Component, Debug)]
+pub struct BoundsCollider {
+	pub half_size: Vec2,
+	pub restitution: f32,		// 0 stops dead against a wall, 1 bounces back at full speed.
+}
+
+// Markers opting a BoundsCollider in to each region.
+#[derive(Component)]
+pub struct CollideWithScreen;
+#[derive(Component)]
+pub struct CollideWithKeyboard;
+
+// Runs in FixedUpdate, after integrate_velocity.
+pub fn resolve_bounds_collisions(
+	bounds: Res<PhysicsBounds>,
+	mut bodies: Query<(
+		&mut PhysicsPosition,
+		&mut LinearVelocity,
+		&BoundsCollider,
+		Has<CollideWithScreen>,
+		Has<CollideWithKeyboard>,
+	)>,
+) {
+	for (mut position, mut velocity, collider, with_screen, with_keyboard) in &mut bodies {
+		if with_screen {
+			keep_inside(&bounds.screen, &mut position, &mut velocity, collider);
+		}
+		if with_keyboard {
+			keep_outside(&bounds.keyboard, &bounds.screen, &mut position, &mut velocity, collider);
+		}
+	}
+}
+
+fn keep_inside(region: &Rect, position: &mut PhysicsPosition, velocity: &mut LinearVelocity, collider: &BoundsCollider) {
+	let min = region.min + collider.half_size;
+	let max = region.max - collider.half_size;
+
+	if position.translation.x < min.x {
+		position.translation.x = min.x;
+		velocity.0.x = velocity.0.x.abs() * collider.restitution;
+	} else if position.translation.x > max.x {
+		position.translation.x = max.x;
+		velocity.0.x = -velocity.0.x.abs() * collider.restitution;
+	}
+
+	if position.translation.y < min.y {
+		position.translation.y = min.y;
+		velocity.0.y = velocity.0.y.abs() * collider.restitution;
+	} else if position.translation.y > max.y {
+		position.translation.y = max.y;
+		velocity.0.y = -velocity.0.y.abs() * collider.restitution;
+	}
+}
+
+fn keep_outside(region: &Rect, within: &Rect, position: &mut PhysicsPosition, velocity: &mut LinearVelocity, collider: &BoundsCollider) {
+	let body = Rect::from_center_half_size(position.translation, collider.half_size);
+	if region.intersect(body).is_empty() {
+		return;
+	}
+
+	// Push out along whichever side needs the smallest correction, skipping sides that would push
+	// the body out of `within` (e.g. the keyboard spans the whole screen width, so only its top face counts).
+	// If every side would, push up.
+	let push_up = Vec2::new(0., region.max.y - body.min.y);
+	let push_down = Vec2::new(0., region.min.y - body.max.y);
+	let push_left = Vec2::new(region.min.x - body.max.x, 0.);
+	let push_right = Vec2::new(region.max.x - body.min.x, 0.);
+	let push = [push_up, push_down, push_left, push_right].into_iter()
+		.filter(|push| {
+			let pushed = Rect::from_center_half_size(position.translation + *push, collider.half_size);
+			within.contains(pushed.min) && within.contains(pushed.max)
+		})
+		.min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+		.unwrap_or(push_up);
+
+	position.translation += push;
+	if push.y > 0. {
+		velocity.0.y = velocity.0.y.abs() * collider.restitution;
+	} else if push.y < 0. {
+		velocity.0.y = -velocity.0.y.abs() * collider.restitution;
+	} else if push.x < 0. {
+		velocity.0.x = -velocity.0.x.abs() * collider.restitution;
+	} else {
+		velocity.0.x = velocity.0.x.abs() * collider.restitution;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn body_at(translation: Vec2) -> PhysicsPosition {
+		PhysicsPosition { translation, ..PhysicsPosition::default() }
+	}
+
+	fn assert_close(actual: Vec2, expected: Vec2) {
+		assert!(actual.distance(expected) < 1e-3, "{actual} isn't {expected}");
+	}
+
+	#[test]
+	fn keep_outside_pushes_up_out_of_the_keyboard() {
+		let bounds = PhysicsBounds::default();
+		let collider = BoundsCollider { half_size: Vec2::splat(10.), restitution: 0.5 };
+		let mut position = body_at(Vec2::new(0., bounds.keyboard.max.y + 5.));
+		let mut velocity = LinearVelocity(Vec2::new(30., -100.));
+
+		keep_outside(&bounds.keyboard, &bounds.screen, &mut position, &mut velocity, &collider);
+
+		assert_close(position.translation, Vec2::new(0., bounds.keyboard.max.y + 10.));
+		assert_close(velocity.0, Vec2::new(30., 50.));
+	}
+
+	#[test]
+	fn keep_outside_never_pushes_sideways_off_the_screen() {
+		// Hanging off the left edge, down in the keyboard: the left face is the nearest way out,
+		// but it's off screen, so the body goes up instead.
+		let bounds = PhysicsBounds::default();
+		let collider = BoundsCollider { half_size: Vec2::splat(10.), restitution: 0. };
+		let start = Vec2::new(bounds.screen.min.x + 2., bounds.keyboard.max.y - 30.);
+		let mut position = body_at(start);
+		let mut velocity = LinearVelocity(Vec2::new(-40., -100.));
+
+		keep_outside(&bounds.keyboard, &bounds.screen, &mut position, &mut velocity, &collider);
+
+		assert_close(position.translation, Vec2::new(start.x, bounds.keyboard.max.y + 10.));
+		assert_close(velocity.0, Vec2::new(-40., 0.));
+	}
+
+	#[test]
+	fn keep_inside_bounces_with_restitution() {
+		let bounds = PhysicsBounds::default();
+		let collider = BoundsCollider { half_size: Vec2::splat(10.), restitution: 0.5 };
+		let mut position = body_at(Vec2::new(bounds.screen.max.x + 5., 0.));
+		let mut velocity = LinearVelocity(Vec2::new(200., 20.));
+
+		keep_inside(&bounds.screen, &mut position, &mut velocity, &collider);
+
+		assert_close(position.translation, Vec2::new(bounds.screen.max.x - 10., 0.));
+		assert_close(velocity.0, Vec2::new(-100., 20.));
+	}
+
+	#[test]
+	fn damping_doesnt_depend_on_step_size() {
+		let damping = Damping { linear: 0.8, angular: 1.5 };
+		let mut one_step = (LinearVelocity(Vec2::new(300., -120.)), AngularVelocity(4.));
+		let mut ten_steps = (LinearVelocity(Vec2::new(300., -120.)), AngularVelocity(4.));
+
+		damping.apply(&mut one_step.0, &mut one_step.1, 0.5);
+		for _ in 0..10 {
+			damping.apply(&mut ten_steps.0, &mut ten_steps.1, 0.05);
+		}
+
+		assert_close(one_step.0.0, ten_steps.0.0);
+		assert!((one_step.1.0 - ten_steps.1.0).abs() < 1e-4);
+		assert_close(one_step.0.0, Vec2::new(300., -120.) * (-0.4_f32).exp());
+	}
+}
@@ -0,0 +1,258 @@
+use bevy::prelude::{
+	Component, Bundle, Resource, Event, On,
+	Entity, Name,
+	Query, Res, ResMut, With, Without,
+	Commands,
+	Time, Transform, Visibility,
+	Text2d, TextFont, TextColor,
+	Color, Vec2, Vec3,
+	default,
+};
+use bevy::color::Alpha;
+use bevy_vector_shapes::prelude::*;
+
+use crate::VIRTUAL_RESOLUTION;
+use crate::cleanup::{Cleanup, PowerCycle, Quit};
+use crate::color_utils::ColorScheme;
+use crate::draft::Draft;
+use crate::keyboard::{Keycap, KeySocket, Detached, reattach_keycap};
+
+// =============================================================================
+// Power cycle: the computer turns off, sits dark, and boots back into the thread
+// =============================================================================
+
+// Triggered to start a power cycle (ignored if one is already underway).
+#[derive(Event, Debug)]
+pub struct StartPowerCycle;
+
+// Triggered once the screen has gone fully dark - the moment things get lost.
+#[derive(Event, Debug)]
+pub struct PowerOut;
+
+// Triggered once the boot sequence is done and the thread is usable again.
+#[derive(Event, Debug)]
+pub struct PowerRestored;
+
+// What survives a power cycle. Anything marked Cleanup<PowerCycle> never does.
+// (The ghost prompt also stays put if the fever stage says it never resets; see ghost_slide.rs.)
+// Cracked glass stays cracked by default; rebooting doesn't fix hardware.
+#[derive(Resource, Debug)]
+pub struct PowerCyclePolicy {
+	pub keep_draft: bool,
+	pub keep_ghost_position: bool,
+	pub keep_detached_keys: bool,
+	pub keep_screen_cracks: bool,
+	pub dark_seconds: f32,
+}
+impl Default for PowerCyclePolicy {
+	fn default() -> Self {
+		Self {
+			keep_draft: false,
+			keep_ghost_position: false,
+			keep_detached_keys: false,
+			keep_screen_cracks: true,
+			dark_seconds: 1.5,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PowerCyclePhase {
+	#[default]
+	On,
+	Collapsing,		// Old-CRT shutoff: the picture squeezes to a line, then to a dot.
+	Dark,
+	Booting,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct PowerCycleState {
+	pub phase: PowerCyclePhase,
+	pub phase_elapsed: f32,
+}
+
+// Run condition for anything that should stop responding while the computer is off.
+pub fn power_on(power_cycle_state: Res<PowerCycleState>) -> bool {
+	power_cycle_state.phase == PowerCyclePhase::On
+}
+
+const COLLAPSE_SECONDS: f32 = 0.45;
+const BOOT_SECONDS: f32 = 2.;
+// Fraction of the collapse spent squeezing to a line (the rest shrinks the line to a dot).
+const COLLAPSE_TO_LINE: f32 = 0.6;
+const CRT_LINE_THICKNESS: f32 = 6.;
+const BOOT_TEXT: &str = "Starting up…";
+const POWER_CYCLE_OVERLAY_Z: f32 = 50.;
+
+#[derive(Component)]
+pub struct PowerCycleOverlay;
+
+// One of the two black bars that close in from the top and bottom edges.
+#[derive(Component)]
+pub struct CrtBar {
+	pub from_top: bool,
+}
+
+#[derive(Component)]
+pub struct CrtLine;
+
+#[derive(Component)]
+pub struct BootText;
+
+// Unit-sized rect, sized through its Transform scale so it can be animated cheaply.
+fn unit_rect(color: Color, transform: Transform) -> impl Bundle {
+	ShapeBundle::rect(
+		&ShapeConfig {
+			color,
+			transform,
+			..ShapeConfig::default_2d()
+		},
+		Vec2::ONE,
+	)
+}
+
+pub fn on_start_power_cycle(
+	_event: On<StartPowerCycle>,
+	mut commands: Commands,
+	mut power_cycle_state: ResMut<PowerCycleState>,
+	color_scheme: Res<ColorScheme>,
+) {
+	if power_cycle_state.phase != PowerCyclePhase::On {
+		return;
+	}
+	power_cycle_state.phase = PowerCyclePhase::Collapsing;
+	power_cycle_state.phase_elapsed = 0.;
+
+	commands.spawn((
+		Name::new("PowerCycleOverlay"),
+		Cleanup::<Quit>::new(),
+		PowerCycleOverlay,
+		Transform::from_xyz(0., 0., POWER_CYCLE_OVERLAY_Z),
+		Visibility::default(),
+	)).with_children(|parent| {
+		for from_top in [true, false] {
+			parent.spawn((
+				Name::new("CrtBar"),
+				CrtBar { from_top },
+				unit_rect(Color::BLACK, Transform::from_scale(Vec3::ZERO)),
+			));
+		}
+		parent.spawn((
+			Name::new("CrtLine"),
+			CrtLine,
+			unit_rect(Color::WHITE, Transform::from_xyz(0., 0., 0.1).with_scale(Vec3::ZERO)),
+		));
+		parent.spawn((
+			Name::new("BootText"),
+			BootText,
+			Text2d::new(BOOT_TEXT),
+			TextFont { font_size: 56., ..default() },
+			TextColor(color_scheme.sys_text_color.with_alpha(0.)),
+			Transform::from_xyz(0., 0., 0.2),
+		));
+	});
+}
+
+pub fn advance_power_cycle(
+	mut commands: Commands,
+	time: Res<Time>,
+	policy: Res<PowerCyclePolicy>,
+	mut power_cycle_state: ResMut<PowerCycleState>,
+	overlays: Query<Entity, With<PowerCycleOverlay>>,
+	mut bars: Query<(&CrtBar, &mut Transform, &mut ShapeFill), Without<CrtLine>>,
+	mut lines: Query<&mut Transform, (With<CrtLine>, Without<CrtBar>)>,
+	mut boot_texts: Query<&mut TextColor, With<BootText>>,
+) {
+	if power_cycle_state.phase == PowerCyclePhase::On {
+		return;
+	}
+	power_cycle_state.phase_elapsed += time.delta_secs();
+	let elapsed = power_cycle_state.phase_elapsed;
+	let screen = VIRTUAL_RESOLUTION.as_vec2();
+
+	match power_cycle_state.phase {
+		PowerCyclePhase::On => {}
+		PowerCyclePhase::Collapsing => {
+			let t = (elapsed / COLLAPSE_SECONDS).min(1.);
+			let squeeze = (t / COLLAPSE_TO_LINE).min(1.);
+			let shrink = ((t - COLLAPSE_TO_LINE) / (1. - COLLAPSE_TO_LINE)).clamp(0., 1.);
+
+			// Bars close in, leaving a sliver in the middle; the sliver glows, then pinches to a dot.
+			let bar_height = screen.y * 0.5 * squeeze * squeeze;
+			for (bar, mut transform, _) in &mut bars {
+				let edge = if bar.from_top { screen.y * 0.5 } else { -screen.y * 0.5 };
+				let toward_center = if bar.from_top { -1. } else { 1. };
+				transform.translation.y = edge + toward_center * bar_height * 0.5;
+				transform.scale = Vec3::new(screen.x, bar_height, 1.);
+			}
+			for mut transform in &mut lines {
+				let glow = if squeeze >= 1. { 1. } else { 0. };
+				let width = screen.x * (1. - shrink).max(CRT_LINE_THICKNESS / screen.x);
+				transform.scale = Vec3::new(width * glow, CRT_LINE_THICKNESS * glow, 1.);
+			}
+
+			if t >= 1. {
+				for mut transform in &mut lines {
+					transform.scale = Vec3::ZERO;
+				}
+				power_cycle_state.phase = PowerCyclePhase::Dark;
+				power_cycle_state.phase_elapsed = 0.;
+				commands.trigger(PowerOut);
+			}
+		}
+		PowerCyclePhase::Dark => {
+			if elapsed >= policy.dark_seconds {
+				power_cycle_state.phase = PowerCyclePhase::Booting;
+				power_cycle_state.phase_elapsed = 0.;
+			}
+		}
+		PowerCyclePhase::Booting => {
+			let t = (elapsed / BOOT_SECONDS).min(1.);
+			// Boot text fades in and out over the first two thirds, then the screen fades back up.
+			let text_alpha = (1. - ((t / 0.66) * 2. - 1.).abs()).max(0.);
+			for mut color in &mut boot_texts {
+				color.0 = color.0.with_alpha(text_alpha);
+			}
+			let fade = ((t - 0.66) / 0.34).clamp(0., 1.);
+			for (_, _, mut fill) in &mut bars {
+				fill.color = Color::BLACK.with_alpha(1. - fade);
+			}
+
+			if t >= 1. {
+				for overlay in &overlays {
+					commands.entity(overlay).despawn();
+				}
+				power_cycle_state.phase = PowerCyclePhase::On;
+				power_cycle_state.phase_elapsed = 0.;
+				commands.trigger(PowerRestored);
+			}
+		}
+	}
+}
+
+// Lose whatever the policy says doesn't survive.
+pub fn on_power_out(
+	_event: On<PowerOut>,
+	mut commands: Commands,
+	policy: Res<PowerCyclePolicy>,
+	mut draft: ResMut<Draft>,
+	doomed: Query<Entity, With<Cleanup<PowerCycle>>>,
+	detached_keycaps: Query<(Entity, &Keycap), With<Detached>>,
+	sockets: Query<&KeySocket>,
+) {
+	for entity in &doomed {
+		commands.entity(entity).despawn();
+	}
+
+	if !policy.keep_draft && !draft.0.is_empty() {
+		draft.0.clear();
+	}
+
+	if !policy.keep_detached_keys {
+		for (entity, keycap) in &detached_keycaps {
+			if let Ok(socket) = sockets.get(keycap.socket) {
+				reattach_keycap(&mut commands, entity, socket.home);
+			}
+		}
+	}
+}
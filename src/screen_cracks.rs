@@ -0,0 +1,254 @@
+use bevy::prelude::{
+	Component, Resource, Event, On,
+	Entity, Name,
+	Query, Res, ResMut, With,
+	Commands,
+	Time, Transform,
+	Color, Vec2, Vec3,
+};
+use bevy_vector_shapes::prelude::*;
+use rand::Rng;
+
+use crate::VIRTUAL_RESOLUTION;
+use crate::cleanup::{Cleanup, Quit};
+use crate::power_cycle::{PowerOut, PowerCyclePolicy};
+
+// =============================================================================
+// Screen cracks: damage to the phone's glass that builds up impact by impact
+// =============================================================================
+
+// Crack state lives in the ScreenCracks resource and the lines are Cleanup<Quit>, so nothing
+// about app state changes (e.g. going in and out of PauseMenu) touches them. Only RepairScreen
+// (and a power cycle, if the policy allows) puts the glass back together.
+
+// Trigger to crack the screen at a point (world space), or somewhere random.
+#[derive(Event, Debug)]
+pub struct CrackScreen(pub Option<Vec2>);
+
+// Trigger to clear every crack.
+#[derive(Event, Debug)]
+pub struct RepairScreen;
+
+#[derive(Resource, Debug)]
+pub struct ScreenCrackSettings {
+	pub spread_seconds: f32,		// Time constant of the spread: most of a crack shows up this quickly, the rest creeps.
+	pub branches: (usize, usize),	// Main branches per impact (min, max).
+	pub reach: (f32, f32),			// Main branch length (min, max).
+	pub distortion_radius: f32,		// Taps this close to a crack get bent.
+	pub distortion: f32,			// How far (at most) a tap right on a crack gets pushed across it.
+}
+impl Default for ScreenCrackSettings {
+	fn default() -> Self {
+		Self {
+			spread_seconds: 4.,
+			branches: (5, 9),
+			reach: (180., 520.),
+			distortion_radius: 50.,
+			distortion: 90.,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CrackSegment {
+	pub start: Vec2,
+	pub end: Vec2,
+	pub starts_at: f32,		// Distance from the impact (along the crack) where this segment begins.
+	pub depth: usize,		// 0 for main branches, more for the splinters off them.
+}
+impl CrackSegment {
+	fn length(&self) -> f32 {
+		self.start.distance(self.end)
+	}
+
+	// The end of the part of this segment the crack has spread over so far, if any.
+	fn grown_end(&self, grown: f32) -> Option<Vec2> {
+		let length = self.length();
+		let along = (grown - self.starts_at).min(length);
+		if along <= 0. || length <= 0. {
+			return None;
+		}
+		Some(self.start.lerp(self.end, along / length))
+	}
+}
+
+#[derive(Debug)]
+pub struct CrackImpact {
+	pub segments: Vec<CrackSegment>,
+	pub reach: f32,			// Distance along the longest path; grown approaches this over time.
+	pub age: f32,
+	pub grown: f32,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct ScreenCracks {
+	pub impacts: Vec<CrackImpact>,
+}
+impl ScreenCracks {
+	// Where a tap at `point` actually lands. Near a crack the glass bends it across the crack,
+	// so it ends up on a neighbouring key.
+	pub fn distort(&self, point: Vec2, settings: &ScreenCrackSettings) -> Vec2 {
+		let mut strongest = Vec2::ZERO;
+		for impact in &self.impacts {
+			for segment in &impact.segments {
+				let Some(end) = segment.grown_end(impact.grown) else {
+					continue;
+				};
+				let along = end - segment.start;
+				let t = ((point - segment.start).dot(along) / along.length_squared()).clamp(0., 1.);
+				let to_point = point - segment.start.lerp(end, t);
+				let distance = to_point.length();
+				if distance >= settings.distortion_radius {
+					continue;
+				}
+				// Push across the crack: to the far side from wherever the tap was.
+				let normal = along.perp().normalize_or_zero();
+				let side = if to_point.dot(normal) >= 0. { -1. } else { 1. };
+				let push = normal * side * settings.distortion * (1. - distance / settings.distortion_radius);
+				if push.length_squared() > strongest.length_squared() {
+					strongest = push;
+				}
+			}
+		}
+		point + strongest
+	}
+}
+
+const SCREEN_CRACK_Z: f32 = 30.;		// Above the thread and keyboard; below the eyelids and power cycle.
+const CRACK_COLOR: Color = Color::srgba(0.92, 0.95, 1., 0.85);
+const CRACK_THICKNESS: [f32; 3] = [3., 2., 1.2];
+const MAX_CRACK_DEPTH: usize = 2;
+
+#[derive(Component)]
+pub struct CrackLine {
+	pub impact: usize,
+	pub segment: usize,
+}
+
+// Walks a jagged branch outwards, now and then splintering off a thinner one.
+fn grow_branch(segments: &mut Vec<CrackSegment>, from: Vec2, angle: f32, length: f32, starts_at: f32, depth: usize) -> f32 {
+	let mut rng = rand::rng();
+	let mut position = from;
+	let mut angle = angle;
+	let mut travelled = 0.;
+	let mut furthest = starts_at;
+	while travelled < length {
+		let step = rng.random_range(18f32..48.).min(length - travelled);
+		angle += rng.random_range(-0.35..0.35);
+		let next = position + Vec2::from_angle(angle) * step;
+		segments.push(CrackSegment { start: position, end: next, starts_at: starts_at + travelled, depth });
+		travelled += step;
+		position = next;
+
+		if depth < MAX_CRACK_DEPTH && rng.random_bool(0.18) {
+			let side = if rng.random_bool(0.5) { 1. } else { -1. };
+			let splinter_angle = angle + side * rng.random_range(0.6..1.2);
+			let splinter_length = (length - travelled) * rng.random_range(0.3..0.7);
+			furthest = furthest.max(grow_branch(segments, position, splinter_angle, splinter_length, starts_at + travelled, depth + 1));
+		}
+	}
+	furthest.max(starts_at + travelled)
+}
+
+fn random_impact_point() -> Vec2 {
+	let mut rng = rand::rng();
+	let half = VIRTUAL_RESOLUTION.as_vec2() * 0.45;
+	Vec2::new(rng.random_range(-half.x..half.x), rng.random_range(-half.y..half.y))
+}
+
+pub fn on_crack_screen(
+	event: On<CrackScreen>,
+	mut commands: Commands,
+	settings: Res<ScreenCrackSettings>,
+	mut cracks: ResMut<ScreenCracks>,
+) {
+	let mut rng = rand::rng();
+	let origin = event.0.unwrap_or_else(random_impact_point);
+
+	let mut segments = Vec::new();
+	let mut reach: f32 = 0.;
+	let branch_count = rng.random_range(settings.branches.0..=settings.branches.1.max(settings.branches.0));
+	let first_angle = rng.random_range(0. ..std::f32::consts::TAU);
+	for branch in 0..branch_count {
+		let angle = first_angle + branch as f32 / branch_count as f32 * std::f32::consts::TAU + rng.random_range(-0.3..0.3);
+		let length = rng.random_range(settings.reach.0..=settings.reach.1.max(settings.reach.0));
+		reach = reach.max(grow_branch(&mut segments, origin, angle, length, 0., 0));
+	}
+
+	let impact = cracks.impacts.len();
+	for (segment_index, segment) in segments.iter().enumerate() {
+		commands.spawn((
+			Name::new("CrackLine"),
+			Cleanup::<Quit>::new(),
+			CrackLine { impact, segment: segment_index },
+			ShapeBundle::line(
+				&ShapeConfig {
+					color: CRACK_COLOR,
+					thickness: CRACK_THICKNESS[segment.depth.min(CRACK_THICKNESS.len() - 1)],
+					transform: Transform::from_xyz(0., 0., SCREEN_CRACK_Z),
+					..ShapeConfig::default_2d()
+				},
+				segment.start.extend(0.),
+				segment.start.extend(0.),
+			),
+		));
+	}
+	cracks.impacts.push(CrackImpact { segments, reach, age: 0., grown: 0. });
+}
+
+pub fn spread_screen_cracks(
+	time: Res<Time>,
+	settings: Res<ScreenCrackSettings>,
+	mut cracks: ResMut<ScreenCracks>,
+	mut lines: Query<(&CrackLine, &mut LineComponent)>,
+) {
+	let mut spreading = false;
+	for impact in &mut cracks.impacts {
+		if impact.grown >= impact.reach {
+			continue;
+		}
+		spreading = true;
+		impact.age += time.delta_secs();
+		// Snaps outwards at first, then creeps; finishes once it's within a hair of its reach.
+		impact.grown = impact.reach * (1. - (-impact.age / settings.spread_seconds.max(f32::EPSILON)).exp());
+		if impact.reach - impact.grown < 1. {
+			impact.grown = impact.reach;
+		}
+	}
+	if !spreading {
+		return;
+	}
+
+	for (crack_line, mut line) in &mut lines {
+		let Some(impact) = cracks.impacts.get(crack_line.impact) else {
+			continue;
+		};
+		let Some(segment) = impact.segments.get(crack_line.segment) else {
+			continue;
+		};
+		let end = segment.grown_end(impact.grown).unwrap_or(segment.start);
+		line.end = Vec3::new(end.x, end.y, 0.);
+	}
+}
+
+pub fn on_repair_screen(
+	_event: On<RepairScreen>,
+	mut commands: Commands,
+	mut cracks: ResMut<ScreenCracks>,
+	lines: Query<Entity, With<CrackLine>>,
+) {
+	for entity in &lines {
+		commands.entity(entity).despawn();
+	}
+	cracks.impacts.clear();
+}
+
+pub fn on_power_out_repair_screen(
+	_event: On<PowerOut>,
+	mut commands: Commands,
+	policy: Res<PowerCyclePolicy>,
+) {
+	if !policy.keep_screen_cracks {
+		commands.trigger(RepairScreen);
+	}
+}
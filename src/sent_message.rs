@@ -3,11 +3,13 @@ use bevy::prelude::{
 	Resource, Res, ResMut,
 	Component, Bundle,
 	Commands,
+	Query, With, Or, Changed, Children,
 	Color,
 	Vec2, Vec3, Vec4,
 };
 use bevy::transform;
 use bevy::transform::components::Transform;
+use bevy::color::Mix;
 use bevy_vector_shapes::prelude::*;
 
 use crate::{VIRTUAL_RESOLUTION, component_utils::*};
@@ -27,6 +29,17 @@ pub struct FontColor(pub Color);
 #[derive(Component, Debug)]
 pub struct BkgColor(pub Color);
 
+// A tint mixed over a bubble's BkgColor, so an effect can recolor one bubble without touching the ColorScheme.
+#[derive(Component, Debug, Default)]
+pub struct BubbleTint {
+	pub color: Color,
+	pub amount: f32,		// 0 is plain BkgColor, 1 is all tint.
+}
+
+// Marks the vector shape drawing a message's bubble (a child of the message entity).
+#[derive(Component, Debug)]
+pub struct BubbleShape;
+
 #[derive(Component, Debug)]
 pub struct IsMine(pub bool);
 
@@ -75,7 +88,7 @@ pub fn spawn_sent_message(
 	commands: &mut Commands,
 	next_index: &mut ResMut<NextIndex>,
 	color_scheme: & Res<ColorScheme>,
-	text: &'static str,
+	text: &str,
 	is_mine: bool,
 	preserve_on_clear: bool,
 	transform_override: Option<Transform>,
@@ -125,7 +138,7 @@ pub fn spawn_sent_message(
 
 	let shape_bundle = ShapeBundle::rect(
 		&ShapeConfig {
-			color: BLUE_BUBBLE_COLOR,
+			color: msg_bundle.bkg_color.0,
 			corner_radii: Vec4::splat(40.),
 			// transform: Transform::from_xyz(0., 0., 0.),
 			..ShapeConfig::default_2d()
@@ -137,7 +150,7 @@ pub fn spawn_sent_message(
 	// let mut entity_commands = commands.spawn(msg_bundle);
 	let mut entity_commands = commands.spawn(msg_bundle);
 	
-	entity_commands.with_child(shape_bundle);
+	entity_commands.with_child((shape_bundle, BubbleShape));
 
 	if preserve_on_clear {
 		entity_commands.insert(PreserveOnClear);
@@ -145,6 +158,24 @@ pub fn spawn_sent_message(
 	next_index.0 += 1;
 }
 
+// Keeps bubble shapes drawn in their message's BkgColor (plus any BubbleTint).
+pub fn sync_bubble_colors(
+	msgs: Query<(&BkgColor, Option<&BubbleTint>, &Children), Or<(Changed<BkgColor>, Changed<BubbleTint>)>>,
+	mut fills: Query<&mut ShapeFill, With<BubbleShape>>,
+) {
+	for (bkg_color, tint, children) in &msgs {
+		let color = match tint {
+			Some(tint) => bkg_color.0.mix(&tint.color, tint.amount.clamp(0., 1.)),
+			None => bkg_color.0,
+		};
+		for child in children.iter() {
+			if let Ok(mut fill) = fills.get_mut(*child) {
+				fill.color = color;
+			}
+		}
+	}
+}
+
 // This Display implementation is only useful for the bundle itself (i.e. when we are spawning a message).
 // Additional utility function below prints message details passed into it piecemeal (can be a subset).
 impl fmt::Display for SentMessageBundle {
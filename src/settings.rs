@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use bevy::prelude::{Resource, Res, ResMut, warn};
+
+use crate::audio::{AudioBusSettings, AUDIO_BUSES};
+use crate::voice_lines::VoiceLineSettings;
+
+// =============================================================================
+// Saved settings: the player's choices, kept between runs
+// =============================================================================
+
+// Plain text, one "key value" per line (# starts a comment), next to the executable
+// (so it doesn't matter where the game is launched from).
+// SavedSettings is loaded before anything else and applied to the settings resources at startup;
+// whatever changes one of those settings stores it back and calls save().
+// A missing or unreadable file just means defaults (e.g. on the web, where there's no file system).
+
+pub const SETTINGS_FILE_NAME: &str = "settings.txt";
+
+pub fn settings_path() -> PathBuf {
+	std::env::current_exe().ok()
+		.and_then(|exe| exe.parent().map(|dir| dir.join(SETTINGS_FILE_NAME)))
+		.unwrap_or_else(|| PathBuf::from(SETTINGS_FILE_NAME))
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct SavedSettings(BTreeMap<String, String>);
+impl SavedSettings {
+	pub fn load() -> Self {
+		let Ok(text) = std::fs::read_to_string(settings_path()) else {
+			return Self::default();
+		};
+		let values = text.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty() && !line.starts_with('#'))
+			.filter_map(|line| line.split_once(char::is_whitespace))
+			.map(|(key, value)| (key.to_string(), value.trim().to_string()))
+			.collect();
+		Self(values)
+	}
+
+	pub fn save(&self) {
+		let mut text = String::from("# Saved settings. Delete this file to go back to the defaults.\n");
+		for (key, value) in &self.0 {
+			text.push_str(&format!("{key} {value}\n"));
+		}
+		let path = settings_path();
+		if let Err(error) = std::fs::write(&path, text) {
+			warn!("Couldn't save settings to {}: {error}", path.display());
+		}
+	}
+
+	pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
+		self.0.get(key).and_then(|value| value.parse().ok())
+	}
+
+	pub fn set(&mut self, key: &str, value: impl ToString) {
+		self.0.insert(key.to_string(), value.to_string());
+	}
+}
+
+// -----------------------------------------------------------------------------
+// Which settings are saved, and under what keys
+// -----------------------------------------------------------------------------
+
+pub fn apply_saved_settings(
+	saved: Res<SavedSettings>,
+	mut bus_settings: ResMut<AudioBusSettings>,
+	mut voice_line_settings: ResMut<VoiceLineSettings>,
+) {
+	for bus in AUDIO_BUSES {
+		let level = bus_settings.level_mut(bus);
+		if let Some(volume) = saved.get::<f32>(&format!("volume.{}", bus.key())) {
+			level.volume = volume.clamp(0., 1.);
+		}
+		if let Some(muted) = saved.get(&format!("muted.{}", bus.key())) {
+			level.muted = muted;
+		}
+	}
+	if let Some(allow_profanity) = saved.get("voice.allow_profanity") {
+		voice_line_settings.allow_profanity = allow_profanity;
+	}
+}
+
+pub fn store_audio_settings(saved: &mut SavedSettings, bus_settings: &AudioBusSettings) {
+	for bus in AUDIO_BUSES {
+		let level = bus_settings.level(bus);
+		saved.set(&format!("volume.{}", bus.key()), level.volume);
+		saved.set(&format!("muted.{}", bus.key()), level.muted);
+	}
+}
+
+pub fn store_voice_line_settings(saved: &mut SavedSettings, voice_line_settings: &VoiceLineSettings) {
+	saved.set("voice.allow_profanity", voice_line_settings.allow_profanity);
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{
+	Component, Resource, Event, On,
+	Entity, Name,
+	Query, Res, ResMut, With,
+	Commands,
+	Time, Transform, Visibility,
+	Text2d, TextFont, TextColor,
+	default,
+};
+use bevy::color::Alpha;
+use rand::seq::IndexedRandom;
+
+use crate::{VIRTUAL_RESOLUTION, DEFAULT_KEYBOARD_HEIGHT};
+use crate::audio::{AudioManager, AudioBus, AudioDucking, Sound};
+use crate::cleanup::{Cleanup, Quit};
+use crate::color_utils::ColorScheme;
+use crate::hurt::Hurt;
+use crate::power_cycle::PowerOut;
+
+// =============================================================================
+// Voice lines: the player reacting out loud, with subtitles
+// =============================================================================
+
+// Something happens, SayLine names the kind of reaction it deserves (a VoiceTag), and one of the
+// lines with that tag is picked - never the one that tag played last, and never while another
+// line's cooldown is still running (so a burst of mistakes gets one "what?", not ten).
+// The line plays on the Voice bus (ducking the music), and its subtitle shows just above the keyboard in sys_text_color.
+// Lines with swearing in them are skipped unless allow_profanity is on.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VoiceTag {
+	Surprise,
+	Dread,
+	Despair,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VoiceLine {
+	pub tag: VoiceTag,
+	pub path: &'static str,		// Relative to assets/.
+	pub subtitle: &'static str,
+	pub profane: bool,
+}
+
+// Trigger to have the player react (ignored while a line's cooldown is running).
+#[derive(Event, Debug)]
+pub struct SayLine {
+	pub tag: VoiceTag,
+}
+
+#[derive(Resource, Debug)]
+pub struct VoiceLineSettings {
+	pub lines: Vec<VoiceLine>,
+	pub allow_profanity: bool,
+	pub cooldown_seconds: f32,				// After a line starts, before the next one may.
+	pub volume: f32,
+	pub subtitle_seconds: f32,				// How long a subtitle stays up, at the least...
+	pub subtitle_seconds_per_char: f32,		// ...plus this much per character.
+}
+impl Default for VoiceLineSettings {
+	fn default() -> Self {
+		Self {
+			lines: vec![
+				VoiceLine { tag: VoiceTag::Surprise, path: "audio/voice/what.ogg", subtitle: "What?", profane: false },
+				VoiceLine { tag: VoiceTag::Surprise, path: "audio/voice/oh_fuck.ogg", subtitle: "Oh fuck.", profane: true },
+				VoiceLine { tag: VoiceTag::Surprise, path: "audio/voice/oh_no.ogg", subtitle: "Oh no.", profane: false },
+				VoiceLine { tag: VoiceTag::Dread, path: "audio/voice/that_cant_be_real.ogg", subtitle: "That can't be real.", profane: false },
+				VoiceLine { tag: VoiceTag::Dread, path: "audio/voice/what_the_hell.ogg", subtitle: "What the hell?", profane: true },
+				VoiceLine { tag: VoiceTag::Despair, path: "audio/voice/nooooo.ogg", subtitle: "Nooooo...", profane: false },
+			],
+			allow_profanity: true,
+			cooldown_seconds: 6.,
+			volume: 1.,
+			subtitle_seconds: 1.5,
+			subtitle_seconds_per_char: 0.06,
+		}
+	}
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct VoiceLineState {
+	pub cooldown_remaining: f32,
+	last_line: HashMap<VoiceTag, &'static str>,		// By path.
+}
+
+#[derive(Component, Debug)]
+pub struct Subtitle {
+	pub remaining: f32,		// Seconds.
+}
+
+const SUBTITLE_Z: f32 = 45.;				// Over the eyelids (it's still heard with eyes shut); under the power cycle.
+const SUBTITLE_FONT_SIZE: f32 = 40.;
+const SUBTITLE_KEYBOARD_GAP: f32 = 40.;
+const SUBTITLE_FADE_SECONDS: f32 = 0.3;
+
+pub fn on_say_line(
+	event: On<SayLine>,
+	mut commands: Commands,
+	mut audio: ResMut<AudioManager>,
+	mut ducking: ResMut<AudioDucking>,
+	settings: Res<VoiceLineSettings>,
+	mut state: ResMut<VoiceLineState>,
+	color_scheme: Res<ColorScheme>,
+	subtitles: Query<Entity, With<Subtitle>>,
+) {
+	if state.cooldown_remaining > 0. {
+		return;
+	}
+	let allowed: Vec<&VoiceLine> = settings.lines.iter()
+		.filter(|line| line.tag == event.tag && (settings.allow_profanity || !line.profane))
+		.collect();
+	// No repeats, unless there's nothing else to say.
+	let last = state.last_line.get(&event.tag).copied();
+	let fresh: Vec<&VoiceLine> = allowed.iter().copied().filter(|line| Some(line.path) != last).collect();
+	let candidates = if fresh.is_empty() { &allowed } else { &fresh };
+	let Some(line) = candidates.choose(&mut rand::rng()).copied() else {
+		return;
+	};
+
+	audio.play(Sound::new(line.path, AudioBus::Voice).with_volume(settings.volume));
+	state.cooldown_remaining = settings.cooldown_seconds;
+	state.last_line.insert(event.tag, line.path);
+
+	// One subtitle at a time. It's up about as long as the line is spoken, so the music ducks for as long.
+	for subtitle in &subtitles {
+		commands.entity(subtitle).despawn();
+	}
+	let seconds = settings.subtitle_seconds + settings.subtitle_seconds_per_char * line.subtitle.chars().count() as f32;
+	ducking.hold(seconds);
+	let y = -(VIRTUAL_RESOLUTION.y as f32) * 0.5 + DEFAULT_KEYBOARD_HEIGHT + SUBTITLE_KEYBOARD_GAP;
+	commands.spawn((
+		Name::new("Subtitle"),
+		Cleanup::<Quit>::new(),
+		Subtitle { remaining: seconds },
+		Text2d::new(line.subtitle),
+		TextFont { font_size: SUBTITLE_FONT_SIZE, ..default() },
+		TextColor(color_scheme.sys_text_color),
+		Transform::from_xyz(0., y, SUBTITLE_Z),
+		Visibility::default(),
+	));
+}
+
+// Counts down the cooldown, and fades out and clears subtitles (keeping them in the current sys_text_color).
+pub fn update_voice_lines(
+	mut commands: Commands,
+	time: Res<Time>,
+	color_scheme: Res<ColorScheme>,
+	mut state: ResMut<VoiceLineState>,
+	mut subtitles: Query<(Entity, &mut Subtitle, &mut TextColor)>,
+) {
+	let dt = time.delta_secs();
+	state.cooldown_remaining = (state.cooldown_remaining - dt).max(0.);
+
+	for (entity, mut subtitle, mut color) in &mut subtitles {
+		subtitle.remaining -= dt;
+		if subtitle.remaining <= 0. {
+			commands.entity(entity).despawn();
+			continue;
+		}
+		let alpha = (subtitle.remaining / SUBTITLE_FADE_SECONDS).min(1.);
+		color.0 = color_scheme.sys_text_color.with_alpha(alpha);
+	}
+}
+
+// -----------------------------------------------------------------------------
+// Reactions
+// -----------------------------------------------------------------------------
+
+pub fn on_hurt_say_line(_event: On<Hurt>, mut commands: Commands) {
+	commands.trigger(SayLine { tag: VoiceTag::Surprise });
+}
+
+pub fn on_power_out_say_line(_event: On<PowerOut>, mut commands: Commands) {
+	commands.trigger(SayLine { tag: VoiceTag::Dread });
+}
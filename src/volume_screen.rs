@@ -0,0 +1,289 @@
+use bevy::prelude::{
+	Component, Bundle, Resource,
+	Entity, Name,
+	Query, Res, ResMut, Single, With, Without,
+	Commands,
+	ButtonInput, KeyCode, MouseButton,
+	Camera, GlobalTransform, Window,
+	Transform, Visibility,
+	Text2d, TextFont, TextColor,
+	Color, Vec2, Vec3,
+	default,
+};
+use bevy_vector_shapes::prelude::*;
+
+use crate::VIRTUAL_RESOLUTION;
+use crate::audio::{AudioBus, AudioBusSettings, AUDIO_BUSES};
+use crate::cleanup::{Cleanup, Quit};
+use crate::color_utils::ColorScheme;
+use crate::double_vision::LayerCamera;
+use crate::keyboard::cursor_world_position;
+use crate::settings::{SavedSettings, store_audio_settings};
+
+// =============================================================================
+// Volume screen: a slider and a mute box for each audio bus
+// =============================================================================
+
+// Backquote opens and closes it (Tab is taken by blinking). While it's open the phone underneath takes no input:
+// drag a slider (or use Left/Right on the selected one, Up/Down to select), click a box (or M) to mute.
+// The choices are saved when it closes (see settings.rs).
+
+pub const VOLUME_SCREEN_KEY: KeyCode = KeyCode::Backquote;
+const VOLUME_STEP: f32 = 0.05;
+
+const VOLUME_SCREEN_Z: f32 = 48.;		// Over everything in the room and on the phone; under the power cycle.
+const ROW_SPACING: f32 = 110.;
+const TRACK_SIZE: Vec2 = Vec2::new(420., 10.);
+const TRACK_X: f32 = -30.;
+const TRACK_HIT_HEIGHT: f32 = 44.;		// Generous, so the slider is easy to grab.
+const KNOB_SIZE: f32 = 28.;
+const MUTE_BOX_SIZE: f32 = 36.;
+const MUTE_BOX_X: f32 = TRACK_X + TRACK_SIZE.x * 0.5 + 60.;
+const LABEL_OFFSET_Y: f32 = 34.;
+const LABEL_FONT_SIZE: f32 = 32.;
+const BACKDROP_COLOR: Color = Color::srgba(0., 0., 0., 0.8);
+const TRACK_COLOR: Color = Color::srgb(0.3, 0.3, 0.3);
+const MUTED_FILL_COLOR: Color = Color::srgb(0.45, 0.45, 0.45);
+const SELECTED_TEXT_COLOR: Color = Color::WHITE;
+const UNSELECTED_TEXT_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+
+#[derive(Resource, Debug, Default)]
+pub struct VolumeScreen {
+	pub open: bool,
+	pub selected: usize,				// Index into AUDIO_BUSES.
+	dragging: Option<AudioBus>,
+}
+
+pub fn volume_screen_closed(volume_screen: Res<VolumeScreen>) -> bool {
+	!volume_screen.open
+}
+
+pub fn volume_screen_open(volume_screen: Res<VolumeScreen>) -> bool {
+	volume_screen.open
+}
+
+#[derive(Component, Debug)]
+pub struct VolumeScreenRoot;
+
+#[derive(Component, Debug)]
+pub struct VolumeSliderFill(pub AudioBus);
+
+#[derive(Component, Debug)]
+pub struct VolumeSliderKnob(pub AudioBus);
+
+#[derive(Component, Debug)]
+pub struct MuteBox(pub AudioBus);
+
+#[derive(Component, Debug)]
+pub struct VolumeLabel(pub AudioBus);
+
+fn row_y(index: usize) -> f32 {
+	((AUDIO_BUSES.len() as f32 - 1.) * 0.5 - index as f32) * ROW_SPACING
+}
+
+fn bus_name(bus: AudioBus) -> &'static str {
+	match bus {
+		AudioBus::Master => "Master",
+		AudioBus::Music => "Music",
+		AudioBus::Sfx => "Effects",
+		AudioBus::Voice => "Voice",
+	}
+}
+
+fn unit_rect(color: Color, transform: Transform) -> impl Bundle {
+	ShapeBundle::rect(
+		&ShapeConfig {
+			color,
+			transform,
+			..ShapeConfig::default_2d()
+		},
+		Vec2::ONE,
+	)
+}
+
+pub fn toggle_volume_screen(
+	mut commands: Commands,
+	keyboard_input: Res<ButtonInput<KeyCode>>,
+	mut volume_screen: ResMut<VolumeScreen>,
+	mut saved: ResMut<SavedSettings>,
+	bus_settings: Res<AudioBusSettings>,
+	roots: Query<Entity, With<VolumeScreenRoot>>,
+) {
+	if !keyboard_input.just_pressed(VOLUME_SCREEN_KEY) {
+		return;
+	}
+	volume_screen.dragging = None;
+
+	if volume_screen.open {
+		volume_screen.open = false;
+		for root in &roots {
+			commands.entity(root).despawn();
+		}
+		save_audio_settings(&mut saved, &bus_settings);
+		return;
+	}
+	volume_screen.open = true;
+
+	let screen = VIRTUAL_RESOLUTION.as_vec2();
+	commands.spawn((
+		Name::new("VolumeScreen"),
+		Cleanup::<Quit>::new(),
+		VolumeScreenRoot,
+		Transform::from_xyz(0., 0., VOLUME_SCREEN_Z),
+		Visibility::default(),
+	)).with_children(|parent| {
+		parent.spawn((
+			Name::new("VolumeScreenBackdrop"),
+			unit_rect(BACKDROP_COLOR, Transform::from_scale(screen.extend(1.))),
+		));
+		for (index, bus) in AUDIO_BUSES.into_iter().enumerate() {
+			let y = row_y(index);
+			parent.spawn((
+				Name::new("VolumeLabel"),
+				VolumeLabel(bus),
+				Text2d::new(bus_name(bus)),
+				TextFont { font_size: LABEL_FONT_SIZE, ..default() },
+				TextColor(UNSELECTED_TEXT_COLOR),
+				Transform::from_xyz(TRACK_X, y + LABEL_OFFSET_Y, 0.1),
+			));
+			parent.spawn((
+				Name::new("VolumeSliderTrack"),
+				unit_rect(TRACK_COLOR, Transform::from_xyz(TRACK_X, y, 0.1).with_scale(TRACK_SIZE.extend(1.))),
+			));
+			parent.spawn((
+				Name::new("VolumeSliderFill"),
+				VolumeSliderFill(bus),
+				unit_rect(Color::WHITE, Transform::from_xyz(TRACK_X, y, 0.2)),
+			));
+			parent.spawn((
+				Name::new("VolumeSliderKnob"),
+				VolumeSliderKnob(bus),
+				unit_rect(Color::WHITE, Transform::from_xyz(TRACK_X, y, 0.3).with_scale(Vec3::new(KNOB_SIZE * 0.5, KNOB_SIZE, 1.))),
+			));
+			parent.spawn((
+				Name::new("MuteBox"),
+				MuteBox(bus),
+				unit_rect(Color::WHITE, Transform::from_xyz(MUTE_BOX_X, y, 0.1).with_scale(Vec3::new(MUTE_BOX_SIZE, MUTE_BOX_SIZE, 1.))),
+			));
+		}
+	});
+}
+
+pub fn volume_screen_input(
+	keyboard_input: Res<ButtonInput<KeyCode>>,
+	mouse_input: Res<ButtonInput<MouseButton>>,
+	window: Single<&Window>,
+	camera: Single<(&Camera, &GlobalTransform), Without<LayerCamera>>,
+	mut volume_screen: ResMut<VolumeScreen>,
+	mut bus_settings: ResMut<AudioBusSettings>,
+	mut saved: ResMut<SavedSettings>,
+) {
+	let count = AUDIO_BUSES.len();
+	if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+		volume_screen.selected = (volume_screen.selected + count - 1) % count;
+	}
+	if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+		volume_screen.selected = (volume_screen.selected + 1) % count;
+	}
+	let selected = AUDIO_BUSES[volume_screen.selected.min(count - 1)];
+	let mut step = 0.;
+	if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+		step -= VOLUME_STEP;
+	}
+	if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+		step += VOLUME_STEP;
+	}
+	if step != 0. {
+		let level = bus_settings.level_mut(selected);
+		level.volume = (level.volume + step).clamp(0., 1.);
+		save_audio_settings(&mut saved, &bus_settings);
+	}
+	if keyboard_input.just_pressed(KeyCode::KeyM) {
+		let level = bus_settings.level_mut(selected);
+		level.muted = !level.muted;
+		save_audio_settings(&mut saved, &bus_settings);
+	}
+
+	if !mouse_input.pressed(MouseButton::Left) {
+		// A dragged slider's volume is saved once it's let go of.
+		if volume_screen.dragging.take().is_some() {
+			save_audio_settings(&mut saved, &bus_settings);
+		}
+		return;
+	}
+	let (camera, camera_transform) = *camera;
+	let Some(pointer) = cursor_world_position(&window, camera, camera_transform) else {
+		return;
+	};
+	if mouse_input.just_pressed(MouseButton::Left) {
+		for (index, bus) in AUDIO_BUSES.into_iter().enumerate() {
+			let y = row_y(index);
+			let on_track = (pointer.x - TRACK_X).abs() <= TRACK_SIZE.x * 0.5 + KNOB_SIZE * 0.5
+				&& (pointer.y - y).abs() <= TRACK_HIT_HEIGHT * 0.5;
+			let on_mute_box = (pointer - Vec2::new(MUTE_BOX_X, y)).abs().max_element() <= MUTE_BOX_SIZE * 0.5;
+			if on_track {
+				volume_screen.dragging = Some(bus);
+				volume_screen.selected = index;
+			} else if on_mute_box {
+				let level = bus_settings.level_mut(bus);
+				level.muted = !level.muted;
+				volume_screen.selected = index;
+				save_audio_settings(&mut saved, &bus_settings);
+			}
+		}
+	}
+	if let Some(bus) = volume_screen.dragging {
+		let volume = ((pointer.x - (TRACK_X - TRACK_SIZE.x * 0.5)) / TRACK_SIZE.x).clamp(0., 1.);
+		let level = bus_settings.level_mut(bus);
+		if level.volume != volume {
+			level.volume = volume;
+		}
+	}
+}
+
+fn save_audio_settings(saved: &mut SavedSettings, bus_settings: &AudioBusSettings) {
+	store_audio_settings(saved, bus_settings);
+	saved.save();
+}
+
+// Redraws the sliders, boxes and labels to match the settings.
+pub fn sync_volume_screen(
+	volume_screen: Res<VolumeScreen>,
+	bus_settings: Res<AudioBusSettings>,
+	color_scheme: Res<ColorScheme>,
+	mut fills: Query<(&VolumeSliderFill, &mut Transform, &mut ShapeFill), (Without<VolumeSliderKnob>, Without<MuteBox>)>,
+	mut knobs: Query<(&VolumeSliderKnob, &mut Transform), (Without<VolumeSliderFill>, Without<MuteBox>)>,
+	mut mute_boxes: Query<(&MuteBox, &mut ShapeFill), Without<VolumeSliderFill>>,
+	mut labels: Query<(&VolumeLabel, &mut Text2d, &mut TextColor)>,
+) {
+	let selected = AUDIO_BUSES.get(volume_screen.selected).copied();
+	let left = TRACK_X - TRACK_SIZE.x * 0.5;
+
+	for (fill, mut transform, mut shape_fill) in &mut fills {
+		let level = bus_settings.level(fill.0);
+		let width = TRACK_SIZE.x * level.volume;
+		transform.translation.x = left + width * 0.5;
+		transform.scale = Vec3::new(width, TRACK_SIZE.y, 1.);
+		shape_fill.color = if level.muted { MUTED_FILL_COLOR } else { color_scheme.my_bubble_color };
+	}
+	for (knob, mut transform) in &mut knobs {
+		transform.translation.x = left + TRACK_SIZE.x * bus_settings.level(knob.0).volume;
+	}
+	for (mute_box, mut shape_fill) in &mut mute_boxes {
+		// Filled in when muted.
+		shape_fill.color = if bus_settings.level(mute_box.0).muted { color_scheme.my_bubble_color } else { TRACK_COLOR };
+	}
+	for (label, mut text, mut color) in &mut labels {
+		let level = bus_settings.level(label.0);
+		let percent = (level.volume * 100.).round();
+		let wanted = if level.muted {
+			format!("{}  {percent}% (muted)", bus_name(label.0))
+		} else {
+			format!("{}  {percent}%", bus_name(label.0))
+		};
+		if text.0 != wanted {
+			text.0 = wanted;
+		}
+		color.0 = if selected == Some(label.0) { SELECTED_TEXT_COLOR } else { UNSELECTED_TEXT_COLOR };
+	}
+}